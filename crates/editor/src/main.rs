@@ -10,7 +10,10 @@ use eframe::egui::{self};
 use sophixer_core::data::{Set, Song};
 use tin_drivers_midi::{MidiDriver, devices::launchpad_mini_mk3::LPM3Driver};
 
-use crate::windows::{Window, set_editor::SetEditor, song_editor::SongEditor, song_new::SongNew};
+use crate::windows::{
+  Window, diagnostics::Diagnostics, set_editor::SetEditor, song_editor::SongEditor,
+  song_new::SongNew,
+};
 
 pub mod widgets;
 pub mod windows;
@@ -95,8 +98,13 @@ impl eframe::App for App {
       if ui.button("Open set…").clicked() {
         if let Some(path) = rfd::FileDialog::new().pick_file() {
           if let Ok(set_string) = read_to_string(path.clone()) {
-            self.model.set = Some(ron::from_str(&set_string).unwrap());
-            self.set_file = Some(path.to_string_lossy().to_string());
+            match Set::load(&set_string) {
+              Ok(set) => {
+                self.model.set = Some(set);
+                self.set_file = Some(path.to_string_lossy().to_string());
+              }
+              Err(e) => error!("couldn't read set: {e}"),
+            }
           } else {
             error!("couldn't load set")
           }
@@ -131,6 +139,9 @@ impl eframe::App for App {
           if ui.button("Set Editor").clicked() {
             self.windows.push((Box::new(SetEditor::default()), true));
           }
+          if ui.button("Diagnostics").clicked() {
+            self.windows.push((Box::new(Diagnostics::default()), true));
+          }
 
           ui.label("songs:");
 
@@ -0,0 +1,59 @@
+use std::mem::discriminant;
+
+use eframe::egui::{ComboBox, DragValue, Ui};
+use sophixer_core::data::follow::FollowAction;
+
+fn kind(value: &FollowAction) -> &'static str {
+  match value {
+    FollowAction::None => "None",
+    FollowAction::Goto(_) => "Goto",
+    FollowAction::Back => "Back",
+    FollowAction::Stop => "Stop",
+    FollowAction::Random(_) => "Random",
+    FollowAction::NextSong => "NextSong",
+  }
+}
+
+pub fn follow_selector(value: &mut FollowAction, ui: &mut Ui) {
+  ComboBox::from_label("follow action")
+    .selected_text(kind(value))
+    .show_ui(ui, |ui| {
+      for sv in [
+        FollowAction::None,
+        FollowAction::Goto(1),
+        FollowAction::Back,
+        FollowAction::Stop,
+        FollowAction::Random(Vec::new()),
+        FollowAction::NextSong,
+      ] {
+        let selected = discriminant(value) == discriminant(&sv);
+        if ui.selectable_label(selected, kind(&sv)).clicked() && !selected {
+          *value = sv;
+        }
+      }
+    });
+
+  match value {
+    FollowAction::Goto(y) => {
+      ui.horizontal(|ui| {
+        ui.label("pattern");
+        ui.add(DragValue::new(y));
+      });
+    }
+    FollowAction::Random(ys) => {
+      ui.horizontal(|ui| {
+        ui.label("patterns");
+        for y in ys.iter_mut() {
+          ui.add(DragValue::new(y));
+        }
+        if ui.button("-").clicked() {
+          ys.pop();
+        }
+        if ui.button("+").clicked() {
+          ys.push(1);
+        }
+      });
+    }
+    _ => {}
+  }
+}
@@ -1 +1,4 @@
 pub mod channel_selector;
+pub mod follow_selector;
+pub mod quantization_selector;
+pub mod reference_selector;
@@ -0,0 +1,17 @@
+use eframe::egui::{ComboBox, Ui};
+use sophixer_core::data::quantization::Quantization;
+
+pub fn quantization_selector(value: &mut Quantization, ui: &mut Ui) {
+  ComboBox::from_label("quantization")
+    .selected_text(format!("{}", value))
+    .show_ui(ui, |ui| {
+      for sv in [
+        Quantization::Off,
+        Quantization::Beat,
+        Quantization::Bar,
+        Quantization::Pattern,
+      ] {
+        ui.selectable_value(value, sv, format!("{}", sv));
+      }
+    });
+}
@@ -0,0 +1,38 @@
+use eframe::egui::{ComboBox, DragValue, Ui};
+use sophixer_core::data::references::Reference;
+
+/// picks a device or parameter either by index or by name
+pub fn reference_selector(label: &str, value: &mut Reference, ui: &mut Ui) {
+  ui.horizontal(|ui| {
+    ui.label(label);
+    ComboBox::from_id_salt(label)
+      .selected_text(match value {
+        Reference::Index(_) => "index",
+        Reference::Name(_) => "name",
+      })
+      .show_ui(ui, |ui| {
+        if ui
+          .selectable_label(matches!(value, Reference::Index(_)), "index")
+          .clicked()
+          && !matches!(value, Reference::Index(_))
+        {
+          *value = Reference::Index(1);
+        }
+        if ui
+          .selectable_label(matches!(value, Reference::Name(_)), "name")
+          .clicked()
+          && !matches!(value, Reference::Name(_))
+        {
+          *value = Reference::Name(String::new());
+        }
+      });
+    match value {
+      Reference::Index(i) => {
+        ui.add(DragValue::new(i));
+      }
+      Reference::Name(name) => {
+        ui.text_edit_singleline(name);
+      }
+    }
+  });
+}
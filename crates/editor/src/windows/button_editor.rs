@@ -1,17 +1,27 @@
-use eframe::egui::{ComboBox, DragValue, color_picker::color_edit_button_srgb};
+use eframe::egui::{ComboBox, DragValue, Ui, color_picker::color_edit_button_srgb};
 use sophixer_core::data::{
   buttons::{
     SongButton, SongButtonAction,
     cycle_effect_parameter_value::{CycleEffectParameterValue, ParameterValue},
+    macros::{Macro, MacroStep},
     play_sample::PlaySample,
+    play_section::PlaySection,
+    script::Script,
     toggle_channels::ToggleChannels,
     toggle_effect_bypass::ToggleEffectBypass,
     toggle_track_patterns::ToggleTrackPatterns,
   },
   channels::Channel,
+  layout::TrackLayout,
 };
 
-use crate::{widgets::channel_selector::channel_selector, windows::Window};
+use crate::{
+  widgets::{
+    channel_selector::channel_selector, quantization_selector::quantization_selector,
+    reference_selector::reference_selector,
+  },
+  windows::Window,
+};
 
 pub struct ButtonEditor {
   song_id: String,
@@ -31,235 +41,330 @@ impl ButtonEditor {
       u64_buffer: 0,
     }
   }
-}
-
-impl Window for ButtonEditor {
-  fn title(&mut self) -> String {
-    format!(
-      "button editor: {}@{},{}",
-      self.song_id, self.pos.0, self.pos.1,
-    )
-  }
 
-  fn ui(
+  /// macro steps can't be scripts, only buttons run them
+  fn action_ui(
     &mut self,
-    model: &mut crate::Model,
-    ui: &mut eframe::egui::Ui,
-  ) -> anyhow::Result<Option<Box<dyn Window>>> {
-    if let Some(set) = &mut model.set {
-      if let Some(song) = set.songs.get_mut(&self.song_id) {
-        ui.heading("manage");
-
-        if ui.button("delete").clicked() {
-          song.buttons.remove(&self.pos);
+    action: &mut SongButtonAction,
+    layout: &TrackLayout,
+    step: bool,
+    ui: &mut Ui,
+  ) {
+    ComboBox::from_label("action type")
+      .selected_text(format!("{}", action))
+      .show_ui(ui, |ui| {
+        ui.selectable_value(
+          action,
+          SongButtonAction::ToggleChannels(ToggleChannels::default()),
+          "ToggleChannels",
+        );
+        ui.selectable_value(
+          action,
+          SongButtonAction::ToggleTrackPatterns(ToggleTrackPatterns::default()),
+          "ToggleTrackPatterns",
+        );
+        ui.selectable_value(
+          action,
+          SongButtonAction::ToggleEffectBypass(ToggleEffectBypass::default()),
+          "ToggleEffectBypass",
+        );
+        ui.selectable_value(
+          action,
+          SongButtonAction::CycleEffectParameterValue(CycleEffectParameterValue::default()),
+          "CycleEffectParameterValue",
+        );
+        ui.selectable_value(
+          action,
+          SongButtonAction::PlaySample(PlaySample::default()),
+          "PlaySample",
+        );
+        ui.selectable_value(
+          action,
+          SongButtonAction::PlaySection(PlaySection::default()),
+          "PlaySection",
+        );
+        ui.selectable_value(action, SongButtonAction::Macro(Macro::default()), "Macro");
+        if !step {
+          ui.selectable_value(
+            action,
+            SongButtonAction::Script(Script::default()),
+            "Script",
+          );
         }
+      });
 
-        if let Some(button) = song.buttons.get_mut(&self.pos) {
-          ComboBox::from_label("action type")
-            .selected_text(format!("{}", button.action))
-            .show_ui(ui, |ui| {
-              ui.selectable_value(
-                &mut button.action,
-                SongButtonAction::ToggleChannels(ToggleChannels::default()),
-                "ToggleChannels",
-              );
-              ui.selectable_value(
-                &mut button.action,
-                SongButtonAction::ToggleTrackPatterns(ToggleTrackPatterns::default()),
-                "ToggleTrackPatterns",
-              );
-              ui.selectable_value(
-                &mut button.action,
-                SongButtonAction::ToggleEffectBypass(ToggleEffectBypass::default()),
-                "ToggleEffectBypass",
-              );
-              ui.selectable_value(
-                &mut button.action,
-                SongButtonAction::CycleEffectParameterValue(CycleEffectParameterValue::default()),
-                "CycleEffectParameterValue",
-              );
-              ui.selectable_value(
-                &mut button.action,
-                SongButtonAction::PlaySample(PlaySample::default()),
-                "PlaySample",
-              );
-            });
+    match action {
+      SongButtonAction::ToggleTrackPatterns(inner) => {
+        // TRACK PATTERNS
 
-          match &mut button.action {
-            SongButtonAction::ToggleTrackPatterns(inner) => {
-              // TRACK PATTERNS
+        ui.heading("info");
 
-              ui.heading("info");
+        ui.checkbox(&mut inner.default, "default");
 
-              ui.checkbox(&mut inner.default, "default");
+        ui.horizontal(|ui| {
+          ui.label("color off");
+          color_edit_button_srgb(ui, &mut inner.color_off);
+        });
 
-              ui.horizontal(|ui| {
-                ui.label("color off");
-                color_edit_button_srgb(ui, &mut inner.color_off);
-              });
+        ui.horizontal(|ui| {
+          ui.label("color on");
+          color_edit_button_srgb(ui, &mut inner.color_on);
+        });
 
-              ui.horizontal(|ui| {
-                ui.label("color on");
-                color_edit_button_srgb(ui, &mut inner.color_on);
-              });
+        ui.heading("track patterns");
 
-              ui.heading("track patterns");
-
-              let tpclone = inner.track_patterns.clone();
-              let mut tps = tpclone.iter().collect::<Vec<&(Channel, u64)>>();
-              tps.sort();
-              for tp in tps {
-                ui.horizontal(|ui| {
-                  ui.label(format!("track {} pos {}", tp.0, tp.1));
-                  if ui.button("remove").clicked() {
-                    inner.track_patterns.remove(tp);
-                  }
-                });
-              }
-              ui.horizontal(|ui| {
-                channel_selector(&mut self.channel_buffer, ui);
-                ui.label("pos");
-                ui.add(DragValue::new(&mut self.u64_buffer));
-                if ui.button("add").clicked() {
-                  inner
-                    .track_patterns
-                    .insert((self.channel_buffer.clone(), self.u64_buffer));
-                }
-              });
+        let tpclone = inner.track_patterns.clone();
+        let mut tps = tpclone.iter().collect::<Vec<&(Channel, u64)>>();
+        tps.sort();
+        for tp in tps {
+          ui.horizontal(|ui| {
+            ui.label(format!("track {} pos {}", tp.0, tp.1));
+            if ui.button("remove").clicked() {
+              inner.track_patterns.remove(tp);
             }
-            SongButtonAction::ToggleChannels(inner) => {
-              // CHANNELS
+          });
+        }
+        ui.horizontal(|ui| {
+          channel_selector(&mut self.channel_buffer, layout, ui);
+          ui.label("pos");
+          ui.add(DragValue::new(&mut self.u64_buffer));
+          if ui.button("add").clicked() {
+            inner
+              .track_patterns
+              .insert((self.channel_buffer.clone(), self.u64_buffer));
+          }
+        });
+      }
+      SongButtonAction::ToggleChannels(inner) => {
+        // CHANNELS
 
-              ui.heading("info");
+        ui.heading("info");
 
-              ui.checkbox(&mut inner.default, "default");
+        ui.checkbox(&mut inner.default, "default");
 
-              ui.horizontal(|ui| {
-                ui.label("color off");
-                color_edit_button_srgb(ui, &mut inner.color_off);
-              });
+        ui.horizontal(|ui| {
+          ui.label("color off");
+          color_edit_button_srgb(ui, &mut inner.color_off);
+        });
 
-              ui.horizontal(|ui| {
-                ui.label("color on");
-                color_edit_button_srgb(ui, &mut inner.color_on);
-              });
+        ui.horizontal(|ui| {
+          ui.label("color on");
+          color_edit_button_srgb(ui, &mut inner.color_on);
+        });
 
-              ui.heading("track patterns");
-
-              let tpclone = inner.channels.clone();
-              let mut tps = tpclone.iter().collect::<Vec<&Channel>>();
-              tps.sort();
-              for tp in tps {
-                ui.horizontal(|ui| {
-                  ui.label(format!("track {}", tp));
-                  if ui.button("remove").clicked() {
-                    inner.channels.remove(tp);
-                  }
-                });
-              }
-              ui.horizontal(|ui| {
-                channel_selector(&mut self.channel_buffer, ui);
-                if ui.button("add").clicked() {
-                  inner.channels.insert(self.channel_buffer.clone());
-                }
-              });
+        ui.heading("track patterns");
+
+        let tpclone = inner.channels.clone();
+        let mut tps = tpclone.iter().collect::<Vec<&Channel>>();
+        tps.sort();
+        for tp in tps {
+          ui.horizontal(|ui| {
+            ui.label(format!("track {}", tp));
+            if ui.button("remove").clicked() {
+              inner.channels.remove(tp);
             }
-            SongButtonAction::ToggleEffectBypass(inner) => {
-              // EFFECT
+          });
+        }
+        ui.horizontal(|ui| {
+          channel_selector(&mut self.channel_buffer, layout, ui);
+          if ui.button("add").clicked() {
+            inner.channels.insert(self.channel_buffer.clone());
+          }
+        });
+      }
+      SongButtonAction::ToggleEffectBypass(inner) => {
+        // EFFECT
 
-              ui.heading("info");
+        ui.heading("info");
 
-              channel_selector(&mut inner.track, ui);
+        channel_selector(&mut inner.track, layout, ui);
 
-              ui.horizontal(|ui| {
-                ui.label("effect");
-                ui.add(DragValue::new(&mut inner.effect));
-              });
+        reference_selector("effect", &mut inner.effect, ui);
 
-              ui.checkbox(&mut inner.default, "default");
+        ui.checkbox(&mut inner.default, "default");
 
-              ui.horizontal(|ui| {
-                ui.label("color off");
-                color_edit_button_srgb(ui, &mut inner.color_off);
-              });
+        ui.horizontal(|ui| {
+          ui.label("color off");
+          color_edit_button_srgb(ui, &mut inner.color_off);
+        });
 
-              ui.horizontal(|ui| {
-                ui.label("color on");
-                color_edit_button_srgb(ui, &mut inner.color_on);
-              });
-            }
-            SongButtonAction::CycleEffectParameterValue(inner) => {
-              // CYCLES
+        ui.horizontal(|ui| {
+          ui.label("color on");
+          color_edit_button_srgb(ui, &mut inner.color_on);
+        });
+      }
+      SongButtonAction::CycleEffectParameterValue(inner) => {
+        // CYCLES
 
-              ui.heading("info");
+        ui.heading("info");
 
-              channel_selector(&mut inner.track, ui);
+        channel_selector(&mut inner.track, layout, ui);
 
-              ui.horizontal(|ui| {
-                ui.label("effect");
-                ui.add(DragValue::new(&mut inner.effect));
-              });
+        reference_selector("effect", &mut inner.effect, ui);
 
-              ui.horizontal(|ui| {
-                ui.label("param");
-                ui.add(DragValue::new(&mut inner.param));
-              });
+        reference_selector("param", &mut inner.param, ui);
 
-              ui.horizontal(|ui| {
-                ui.label("default");
+        ui.horizontal(|ui| {
+          ui.label("default");
 
-                let range_max = match inner.cycles.len() {
-                  0 => 0,
-                  a => a - 1,
-                };
-                ui.add(DragValue::new(&mut inner.default).range(0..=range_max));
-              });
+          let range_max = match inner.cycles.len() {
+            0 => 0,
+            a => a - 1,
+          };
+          ui.add(DragValue::new(&mut inner.default).range(0..=range_max));
+        });
+
+        ui.heading("cycles");
+
+        for c in inner.cycles.iter_mut() {
+          ui.horizontal(|ui| {
+            ui.label("value");
+            ui.add(DragValue::new(&mut c.value).speed(0.05));
+            ui.label("color");
+            color_edit_button_srgb(ui, &mut c.color);
+          });
+        }
+        ui.horizontal(|ui| {
+          if ui.button("-").clicked() && inner.cycles.len() > 0 {
+            inner.cycles.pop();
+          }
+          if ui.button("+").clicked() {
+            inner.cycles.push(ParameterValue::default());
+          }
+        });
+      }
+      SongButtonAction::PlaySample(inner) => {
+        ui.heading("info");
+
+        channel_selector(&mut inner.track, layout, ui);
+
+        ui.horizontal(|ui| {
+          ui.label("pitch");
+          ui.add(DragValue::new(&mut inner.pitch));
+        });
+
+        ui.horizontal(|ui| {
+          ui.label("volume");
+          ui.add(DragValue::new(&mut inner.volume));
+        });
+
+        ui.horizontal(|ui| {
+          ui.label("sample");
+          ui.add(DragValue::new(&mut inner.sample));
+        });
+
+        ui.horizontal(|ui| {
+          ui.label("color");
+          color_edit_button_srgb(ui, &mut inner.color);
+        });
+      }
+      SongButtonAction::PlaySection(inner) => {
+        ui.heading("info");
+
+        ui.horizontal(|ui| {
+          ui.label("start");
+          ui.add(DragValue::new(&mut inner.start));
+        });
+        ui.horizontal(|ui| {
+          ui.label("loop start");
+          ui.add(DragValue::new(&mut inner.loop_start));
+        });
+        ui.horizontal(|ui| {
+          ui.label("loop end");
+          ui.add(DragValue::new(&mut inner.loop_end));
+        });
+
+        ui.checkbox(&mut inner.force, "force");
+
+        ui.horizontal(|ui| {
+          ui.label("color");
+          color_edit_button_srgb(ui, &mut inner.color);
+        });
+      }
+      SongButtonAction::Macro(inner) => {
+        // MACRO
+
+        ui.heading("info");
+
+        ui.checkbox(&mut inner.default, "default");
+
+        ui.horizontal(|ui| {
+          ui.label("color off");
+          color_edit_button_srgb(ui, &mut inner.color_off);
+        });
 
-              ui.heading("cycles");
+        ui.horizontal(|ui| {
+          ui.label("color on");
+          color_edit_button_srgb(ui, &mut inner.color_on);
+        });
 
-              for c in inner.cycles.iter_mut() {
-                ui.horizontal(|ui| {
-                  ui.label("value");
-                  ui.add(DragValue::new(&mut c.value).speed(0.05));
-                  ui.label("color");
-                  color_edit_button_srgb(ui, &mut c.color);
-                });
-              }
+        ui.heading("steps");
+
+        let mut removed = None;
+        for (i, step) in inner.steps.iter_mut().enumerate() {
+          ui.push_id(i, |ui| {
+            ui.group(|ui| {
               ui.horizontal(|ui| {
-                if ui.button("-").clicked() && inner.cycles.len() > 0 {
-                  inner.cycles.pop();
-                }
-                if ui.button("+").clicked() {
-                  inner.cycles.push(ParameterValue::default());
+                ui.label(format!("step {}", i));
+                ui.label("delay (ms)");
+                ui.add(DragValue::new(&mut step.delay));
+                if ui.button("remove").clicked() {
+                  removed = Some(i);
                 }
               });
-            }
-            SongButtonAction::PlaySample(inner) => {
-              ui.heading("info");
+              self.action_ui(&mut step.action, layout, true, ui);
+            });
+          });
+        }
+        if let Some(i) = removed {
+          inner.steps.remove(i);
+        }
+        if ui.button("+").clicked() {
+          inner.steps.push(MacroStep::default());
+        }
+      }
+      SongButtonAction::Script(inner) => {
+        ui.heading("info");
+
+        ui.horizontal(|ui| {
+          ui.label("file");
+          ui.text_edit_singleline(&mut inner.file);
+        });
+
+        ui.horizontal(|ui| {
+          ui.label("color");
+          color_edit_button_srgb(ui, &mut inner.color);
+        });
+      }
+    }
+  }
+}
 
-              channel_selector(&mut inner.track, ui);
+impl Window for ButtonEditor {
+  fn title(&mut self) -> String {
+    format!(
+      "button editor: {}@{},{}",
+      self.song_id, self.pos.0, self.pos.1,
+    )
+  }
 
-              ui.horizontal(|ui| {
-                ui.label("pitch");
-                ui.add(DragValue::new(&mut inner.pitch));
-              });
+  fn ui(
+    &mut self,
+    model: &mut crate::Model,
+    ui: &mut eframe::egui::Ui,
+  ) -> anyhow::Result<Option<Box<dyn Window>>> {
+    if let Some(set) = &mut model.set {
+      if let Some(song) = set.songs.get_mut(&self.song_id) {
+        ui.heading("manage");
 
-              ui.horizontal(|ui| {
-                ui.label("volume");
-                ui.add(DragValue::new(&mut inner.volume));
-              });
+        if ui.button("delete").clicked() {
+          song.buttons.remove(&self.pos);
+        }
 
-              ui.horizontal(|ui| {
-                ui.label("sample");
-                ui.add(DragValue::new(&mut inner.sample));
-              });
+        if let Some(button) = song.buttons.get_mut(&self.pos) {
+          quantization_selector(&mut button.quantization, ui);
 
-              ui.horizontal(|ui| {
-                ui.label("color");
-                color_edit_button_srgb(ui, &mut inner.color);
-              });
-            }
-          }
+          self.action_ui(&mut button.action, &set.layout, false, ui);
         }
       }
     }
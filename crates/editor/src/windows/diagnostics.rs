@@ -0,0 +1,35 @@
+use eframe::egui::{Color32, RichText};
+use sophixer_core::data::validation::Severity;
+
+use crate::windows::Window;
+
+#[derive(Default)]
+pub struct Diagnostics {}
+
+impl Window for Diagnostics {
+  fn title(&mut self) -> String {
+    String::from("set diagnostics")
+  }
+
+  fn ui(
+    &mut self,
+    model: &mut crate::Model,
+    ui: &mut eframe::egui::Ui,
+  ) -> anyhow::Result<Option<Box<dyn Window>>> {
+    if let Some(set) = &model.set {
+      let diagnostics = set.validate();
+      if diagnostics.is_empty() {
+        ui.label("no problems found");
+      }
+      for d in diagnostics {
+        let color = match d.severity {
+          Severity::Warning => Color32::YELLOW,
+          Severity::Error => Color32::RED,
+        };
+        ui.label(RichText::new(format!("{}", d)).color(color));
+      }
+    }
+
+    Ok(None)
+  }
+}
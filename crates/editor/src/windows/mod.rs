@@ -3,6 +3,7 @@ use anyhow::Result;
 use crate::Model;
 
 pub mod button_editor;
+pub mod diagnostics;
 pub mod pattern_editor;
 pub mod set_editor;
 pub mod song_editor;
@@ -1,7 +1,10 @@
 use eframe::egui::{DragValue, color_picker::color_edit_button_srgb};
 use sophixer_core::data::SongPattern;
 
-use crate::windows::Window;
+use crate::{
+  widgets::{follow_selector::follow_selector, quantization_selector::quantization_selector},
+  windows::Window,
+};
 
 pub struct PatternEditor {
   song_id: String,
@@ -53,6 +56,15 @@ impl Window for PatternEditor {
             ui.label("color");
             color_edit_button_srgb(ui, &mut pattern.color);
           });
+          quantization_selector(&mut pattern.quantization, ui);
+
+          ui.heading("follow");
+
+          ui.horizontal(|ui| {
+            ui.label("loops");
+            ui.add(DragValue::new(&mut pattern.loops));
+          });
+          follow_selector(&mut pattern.follow, ui);
         }
       }
     }
@@ -1,4 +1,5 @@
-use eframe::egui::DragValue;
+use eframe::egui::{ComboBox, DragValue};
+use sophixer_core::data::layout::{TrackGroup, TrackSend};
 
 use crate::windows::Window;
 
@@ -37,6 +38,125 @@ impl Window for SetEditor {
           ui.add(DragValue::new(&mut set.stop_seq_pos));
         });
       }
+
+      {
+        ui.heading("setlist");
+        ui.label("songs sorted by order when empty");
+
+        let mut song_ids = set.songs.keys().cloned().collect::<Vec<String>>();
+        song_ids.sort();
+
+        let mut removed = None;
+        for (i, song_id) in set.setlist.iter_mut().enumerate() {
+          ui.push_id(("setlist", i), |ui| {
+            ui.horizontal(|ui| {
+              ui.label(format!("{}", i + 1));
+              ComboBox::from_id_salt("song")
+                .selected_text(song_id.as_str())
+                .show_ui(ui, |ui| {
+                  for id in &song_ids {
+                    ui.selectable_value(song_id, id.clone(), id);
+                  }
+                });
+              if ui.button("remove").clicked() {
+                removed = Some(i);
+              }
+            });
+          });
+        }
+        if let Some(i) = removed {
+          set.setlist.remove(i);
+        }
+        if ui.button("add song").clicked()
+          && let Some(first) = song_ids.first()
+        {
+          set.setlist.push(first.clone());
+        }
+      }
+
+      {
+        ui.heading("layout");
+
+        ui.horizontal(|ui| {
+          ui.label("master track");
+          ui.add(DragValue::new(&mut set.layout.master));
+        });
+
+        let mut removed = None;
+        for (i, group) in set.layout.groups.iter_mut().enumerate() {
+          ui.push_id(i, |ui| {
+            ui.group(|ui| {
+              ui.horizontal(|ui| {
+                ui.label("group");
+                ui.text_edit_singleline(&mut group.name);
+                if ui.button("remove").clicked() {
+                  removed = Some(i);
+                }
+              });
+
+              ui.horizontal(|ui| {
+                ui.label("tracks");
+                for track in group.tracks.iter_mut() {
+                  ui.add(DragValue::new(track));
+                }
+                if ui.button("-").clicked() {
+                  group.tracks.pop();
+                }
+                if ui.button("+").clicked() {
+                  let next = group.tracks.last().map(|t| t + 1).unwrap_or(1);
+                  group.tracks.push(next);
+                }
+              });
+
+              ui.horizontal(|ui| {
+                let mut has_master = group.master.is_some();
+                ui.checkbox(&mut has_master, "master track");
+                match (has_master, &mut group.master) {
+                  (true, Some(master)) => {
+                    ui.add(DragValue::new(master));
+                  }
+                  (true, None) => group.master = Some(1),
+                  (false, _) => group.master = None,
+                }
+              });
+            });
+          });
+        }
+        if let Some(i) = removed {
+          set.layout.groups.remove(i);
+        }
+        if ui.button("add group").clicked() {
+          set.layout.groups.push(TrackGroup {
+            name: String::from("new"),
+            tracks: Vec::new(),
+            master: None,
+          });
+        }
+
+        let mut removed = None;
+        for (i, send) in set.layout.sends.iter_mut().enumerate() {
+          ui.push_id(("send", i), |ui| {
+            ui.horizontal(|ui| {
+              ui.label("send");
+              ui.text_edit_singleline(&mut send.name);
+              ui.label("track");
+              ui.add(DragValue::new(&mut send.track));
+              if ui.button("remove").clicked() {
+                removed = Some(i);
+              }
+            });
+          });
+        }
+        if let Some(i) = removed {
+          set.layout.sends.remove(i);
+        }
+        if ui.button("add send").clicked() {
+          set.layout.sends.push(TrackSend {
+            name: String::from("new"),
+            track: 1,
+          });
+        }
+      }
     }
 
     Ok(None)
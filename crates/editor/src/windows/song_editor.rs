@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use eframe::egui::{DragValue, color_picker::color_edit_button_srgb};
 use sophixer_core::data::{
   Song, SongPattern,
@@ -8,13 +10,19 @@ use tin_drivers_midi::{
   devices::launchpad_mini_mk3::{LPM3Position, LPM3Visual},
 };
 
-use crate::windows::{Window, button_editor::ButtonEditor, pattern_editor::PatternEditor};
+use crate::{
+  widgets::quantization_selector::quantization_selector,
+  windows::{Window, button_editor::ButtonEditor, pattern_editor::PatternEditor},
+};
 
 pub struct SongEditor {
   song_id: String,
   new_pattern: i64,
   new_button_x: i64,
   new_button_y: i64,
+  new_group: String,
+  new_member_x: i64,
+  new_member_y: i64,
 }
 
 impl SongEditor {
@@ -24,6 +32,9 @@ impl SongEditor {
       new_pattern: 1,
       new_button_x: 1,
       new_button_y: 1,
+      new_group: String::new(),
+      new_member_x: 1,
+      new_member_y: 1,
     }
   }
 }
@@ -101,6 +112,45 @@ impl<'a> Window for SongEditor {
           color_edit_button_srgb(ui, &mut song.color);
         });
 
+        ui.horizontal(|ui| {
+          ui.label("bpm");
+          ui.add(DragValue::new(&mut song.bpm).speed(0.5));
+        });
+
+        ui.horizontal(|ui| {
+          let mut has_file = song.file.is_some();
+          ui.checkbox(&mut has_file, "renoise file");
+          match (has_file, &mut song.file) {
+            (true, Some(file)) => {
+              ui.text_edit_singleline(file);
+            }
+            (true, None) => song.file = Some(String::new()),
+            (false, _) => song.file = None,
+          }
+        });
+
+        ui.heading("transition");
+
+        quantization_selector(&mut song.transition.quantization, ui);
+        ui.checkbox(&mut song.transition.sync_bpm, "sync bpm");
+        if song.transition.sync_bpm {
+          ui.horizontal(|ui| {
+            ui.label("bpm ramp (ms)");
+            ui.add(DragValue::new(&mut song.transition.bpm_ramp));
+          });
+        }
+        ui.horizontal(|ui| {
+          let mut has_pattern = song.transition.pattern.is_some();
+          ui.checkbox(&mut has_pattern, "launch pattern");
+          match (has_pattern, &mut song.transition.pattern) {
+            (true, Some(pattern)) => {
+              ui.add(DragValue::new(pattern));
+            }
+            (true, None) => song.transition.pattern = Some(1),
+            (false, _) => song.transition.pattern = None,
+          }
+        });
+
         ui.heading("patterns");
 
         let mut sorted_patterns = song.patterns.keys().map(|f| *f).collect::<Vec<i64>>();
@@ -157,6 +207,72 @@ impl<'a> Window for SongEditor {
             );
           }
         });
+
+        ui.heading("scenes");
+        ui.label("saved from the matrix");
+
+        let mut removed = None;
+        for (i, scene) in song.scenes.iter_mut().enumerate() {
+          ui.push_id(("scene", i), |ui| {
+            ui.horizontal(|ui| {
+              ui.label(format!("pad {}", i + 4));
+              ui.text_edit_singleline(&mut scene.name);
+              ui.label(format!("{} button(s)", scene.states.len()));
+              if ui.button("remove").clicked() {
+                removed = Some(i);
+              }
+            });
+          });
+        }
+        if let Some(i) = removed {
+          song.scenes.remove(i);
+        }
+
+        ui.heading("groups");
+
+        let mut sorted_groups = song.groups.keys().cloned().collect::<Vec<String>>();
+        sorted_groups.sort();
+
+        for group_id in sorted_groups {
+          ui.push_id(&group_id, |ui| {
+            ui.horizontal(|ui| {
+              ui.label(format!("group {}", group_id));
+              if ui.button("delete").clicked() {
+                song.groups.remove(&group_id);
+              }
+            });
+
+            if let Some(members) = song.groups.get_mut(&group_id) {
+              let mut sorted_members = members.iter().copied().collect::<Vec<(i64, i64)>>();
+              sorted_members.sort();
+              for member in sorted_members {
+                ui.horizontal(|ui| {
+                  ui.label(format!("button @ pos {},{}", member.0, member.1));
+                  if ui.button("remove").clicked() {
+                    members.remove(&member);
+                  }
+                });
+              }
+              ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut self.new_member_x));
+                ui.add(DragValue::new(&mut self.new_member_y));
+                if ui.button("add").clicked() {
+                  members.insert((self.new_member_x, self.new_member_y));
+                }
+              });
+            }
+          });
+        }
+
+        ui.horizontal(|ui| {
+          ui.text_edit_singleline(&mut self.new_group);
+          if ui.button("..new").clicked() && !self.new_group.is_empty() {
+            song
+              .groups
+              .entry(self.new_group.clone())
+              .or_insert_with(HashSet::new);
+          }
+        });
       }
     }
 
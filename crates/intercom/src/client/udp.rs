@@ -1,10 +1,10 @@
 use crate::client::InterClient;
-use crate::InterError;
+use crate::{InterError, POLL_INTERVAL};
 use log::{error, trace};
 use std::collections::VecDeque;
 use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{mpsc, Arc};
+use std::sync::{Arc, mpsc};
 use std::thread;
 use std::thread::JoinHandle;
 
@@ -44,7 +44,11 @@ fn udp_reader(
           }
         }
       }
-      Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+      Err(ref e)
+        if matches!(
+          e.kind(),
+          std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+        ) => {}
       Err(e) => {
         error!("udp error: {e:?}");
       }
@@ -59,7 +63,7 @@ fn udp_sender(
   stop_flag: Arc<AtomicBool>,
 ) {
   while !stop_flag.load(Ordering::Relaxed) {
-    match rx.try_recv() {
+    match rx.recv_timeout(POLL_INTERVAL) {
       Ok(msg) => match socket.send_to(msg.as_bytes(), server_addr) {
         Ok(_) => {
           trace!("sent {} to server", msg);
@@ -68,8 +72,8 @@ fn udp_sender(
           error!("couldn't send message on socket: {e:?}");
         }
       },
-      Err(mpsc::TryRecvError::Empty) => {}
-      Err(mpsc::TryRecvError::Disconnected) => {
+      Err(mpsc::RecvTimeoutError::Timeout) => {}
+      Err(mpsc::RecvTimeoutError::Disconnected) => {
         break;
       }
     }
@@ -79,7 +83,9 @@ fn udp_sender(
 impl InterClient for UdpClient {
   fn start(addr: &str) -> Result<Self, InterError> {
     let socket = UdpSocket::bind("0.0.0.0:0").map_err(InterError::IOError)?;
-    socket.set_nonblocking(true).map_err(InterError::IOError)?;
+    socket
+      .set_read_timeout(Some(POLL_INTERVAL))
+      .map_err(InterError::IOError)?;
 
     let server_addr = addr
       .to_socket_addrs()
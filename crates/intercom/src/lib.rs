@@ -5,10 +5,15 @@
 //! - Bismuth
 
 pub mod client;
+pub mod peer;
 pub mod server;
 
+use std::time::Duration;
 use thiserror::Error;
 
+/// how long socket threads wait for something to do before checking whether they should stop
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 /// error
 #[derive(Error, Debug)]
 pub enum InterError {
@@ -0,0 +1,45 @@
+pub mod udp;
+
+use log::warn;
+use std::{collections::VecDeque, net::SocketAddr};
+
+use crate::{InterError, InterMessageIncoming, InterMessageOutgoing, InterMessagePrefixed};
+
+/// member of a group where every message goes to every member, including its sender
+pub trait InterPeer: Sized {
+  fn join(group: &str) -> Result<Self, InterError>;
+  fn leave(self) -> Result<(), InterError>;
+  fn broadcast(&self, msg: String) -> Result<(), InterError>;
+  fn fetch(&mut self) -> Result<(), InterError>;
+  fn get(&self, prefix: String) -> Option<&VecDeque<(SocketAddr, String)>>;
+}
+
+pub trait InterPeerCommunicator<
+  P: InterPeer,
+  I: InterMessageIncoming + InterMessagePrefixed,
+  O: InterMessageOutgoing + InterMessagePrefixed,
+>
+{
+  fn get_messages(peer: &P) -> Option<VecDeque<(SocketAddr, I)>> {
+    peer.get(I::get_prefix()).map(|deque| {
+      let mut deque_clone = deque.clone();
+      let mut r = VecDeque::new();
+      while let Some((addr, msg_string)) = deque_clone.pop_front() {
+        match I::from_raw(msg_string.split(",").collect()) {
+          None => {
+            warn!("unrecognized message from peer: {msg_string:?}")
+          }
+          Some(msg) => {
+            r.push_back((addr, msg));
+          }
+        }
+      }
+      r
+    })
+  }
+  fn broadcast_message(peer: &P, msg: O) -> Result<(), InterError> {
+    let msg_string = msg.to_raw()?;
+    peer.broadcast(O::get_prefix() + ":" + &msg_string + ";")?;
+    Ok(())
+  }
+}
@@ -0,0 +1,205 @@
+use crate::peer::InterPeer;
+use crate::{InterError, POLL_INTERVAL};
+use log::{error, warn};
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+use std::collections::{HashMap, VecDeque};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::thread::JoinHandle;
+
+type InternalSignal = (SocketAddr, String);
+
+/// peer of a udp multicast group
+///
+/// several peers can join the same group on the same machine
+pub struct UdpPeer {
+  stop_flag: Arc<AtomicBool>,
+
+  handle_reader: JoinHandle<()>,
+  handle_sender: JoinHandle<()>,
+
+  rx_reader: mpsc::Receiver<InternalSignal>,
+  tx_sender: mpsc::Sender<String>,
+
+  messages: HashMap<String, VecDeque<(SocketAddr, String)>>,
+}
+
+fn udp_reader(socket: UdpSocket, tx: mpsc::Sender<InternalSignal>, stop_flag: Arc<AtomicBool>) {
+  let mut buf = [0; 1024];
+  while !stop_flag.load(Ordering::Relaxed) {
+    match socket.recv_from(&mut buf) {
+      Ok((len, src)) => {
+        let msg = String::from_utf8_lossy(&buf[..len]).to_string();
+        match tx.send((src, msg)) {
+          Ok(()) => {}
+          Err(e) => {
+            error!("mpsc send error: {e:?}");
+          }
+        }
+      }
+      Err(ref e)
+        if matches!(
+          e.kind(),
+          std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+        ) => {}
+      Err(e) => {
+        error!("udp error: {e:?}");
+      }
+    }
+  }
+}
+
+fn udp_sender(
+  socket: UdpSocket,
+  group: SocketAddrV4,
+  rx: mpsc::Receiver<String>,
+  stop_flag: Arc<AtomicBool>,
+) {
+  while !stop_flag.load(Ordering::Relaxed) {
+    match rx.recv_timeout(POLL_INTERVAL) {
+      Ok(msg) => match socket.send_to(msg.as_bytes(), group) {
+        Ok(_) => {}
+        Err(e) => {
+          error!("couldn't send message on socket: {e:?}");
+        }
+      },
+      Err(mpsc::RecvTimeoutError::Timeout) => {}
+      Err(mpsc::RecvTimeoutError::Disconnected) => {
+        break;
+      }
+    }
+  }
+
+  // what was broadcast before leaving still goes out, like goodbyes
+  while let Ok(msg) = rx.try_recv() {
+    if let Err(e) = socket.send_to(msg.as_bytes(), group) {
+      error!("couldn't send message on socket: {e:?}");
+    }
+  }
+}
+
+impl UdpPeer {
+  /// joins a group on a given interface, the loopback one keeps the group on this machine
+  pub fn join_on(group: &str, interface: Ipv4Addr) -> Result<Self, InterError> {
+    let group = match group
+      .to_socket_addrs()
+      .map_err(InterError::IOError)?
+      .next()
+      .ok_or(InterError::NoSocketAddr(group.to_string()))?
+    {
+      SocketAddr::V4(group) if group.ip().is_multicast() => group,
+      _ => {
+        return Err(InterError::NoSocketAddr(format!(
+          "{group} is not a multicast address"
+        )));
+      }
+    };
+
+    let socket =
+      Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP)).map_err(InterError::IOError)?;
+    socket
+      .set_reuse_address(true)
+      .map_err(InterError::IOError)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true).map_err(InterError::IOError)?;
+    socket
+      .bind(&SockAddr::from(SocketAddrV4::new(
+        Ipv4Addr::UNSPECIFIED,
+        group.port(),
+      )))
+      .map_err(InterError::IOError)?;
+
+    socket
+      .join_multicast_v4(group.ip(), &interface)
+      .map_err(InterError::IOError)?;
+    socket
+      .set_multicast_loop_v4(true)
+      .map_err(InterError::IOError)?;
+    if !interface.is_unspecified() {
+      socket
+        .set_multicast_if_v4(&interface)
+        .map_err(InterError::IOError)?;
+    }
+    socket
+      .set_read_timeout(Some(POLL_INTERVAL))
+      .map_err(InterError::IOError)?;
+    let socket: UdpSocket = socket.into();
+
+    let stop_flag = Arc::new(AtomicBool::new(false));
+
+    let socket_reader = socket.try_clone().map_err(InterError::IOError)?;
+    let (tx_reader, rx_reader) = mpsc::channel::<InternalSignal>();
+    let stop_flag_reader = Arc::clone(&stop_flag);
+    let handle_reader =
+      thread::spawn(move || udp_reader(socket_reader, tx_reader, stop_flag_reader));
+
+    let socket_sender = socket.try_clone().map_err(InterError::IOError)?;
+    let (tx_sender, rx_sender) = mpsc::channel::<String>();
+    let stop_flag_sender = Arc::clone(&stop_flag);
+    let handle_sender =
+      thread::spawn(move || udp_sender(socket_sender, group, rx_sender, stop_flag_sender));
+
+    Ok(Self {
+      stop_flag,
+
+      handle_reader,
+      handle_sender,
+
+      rx_reader,
+      tx_sender,
+
+      messages: HashMap::new(),
+    })
+  }
+}
+
+impl InterPeer for UdpPeer {
+  fn join(group: &str) -> Result<Self, InterError> {
+    Self::join_on(group, Ipv4Addr::UNSPECIFIED)
+  }
+
+  fn leave(self) -> Result<(), InterError> {
+    self.stop_flag.store(true, Ordering::Relaxed);
+
+    self
+      .handle_reader
+      .join()
+      .map_err(|e| InterError::ThreadError(format!("{:?}", e)))?;
+    self
+      .handle_sender
+      .join()
+      .map_err(|e| InterError::ThreadError(format!("{:?}", e)))?;
+
+    Ok(())
+  }
+
+  fn broadcast(&self, msg: String) -> Result<(), InterError> {
+    self
+      .tx_sender
+      .send(msg)
+      .map_err(|e| InterError::MPSCSendError(format!("{e:?}")))
+  }
+
+  fn fetch(&mut self) -> Result<(), InterError> {
+    self.messages.clear();
+    while let Ok((addr, msg)) = self.rx_reader.try_recv() {
+      match msg.strip_suffix(";").and_then(|m| m.split_once(":")) {
+        Some((msg_prefix, msg_content)) => self
+          .messages
+          .entry(msg_prefix.to_string())
+          .or_default()
+          .push_back((addr, msg_content.to_string())),
+        None => {
+          warn!("invalid message received: contained no prefix");
+        }
+      }
+    }
+    Ok(())
+  }
+
+  fn get(&self, prefix: String) -> Option<&VecDeque<(SocketAddr, String)>> {
+    self.messages.get(&prefix)
+  }
+}
@@ -1,14 +1,15 @@
 use crate::server::InterServer;
-use crate::InterError;
+use crate::{InterError, POLL_INTERVAL};
 use log::{error, warn};
 use std::collections::{HashMap, VecDeque};
 use std::net::{SocketAddr, UdpSocket};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{mpsc, Arc};
+use std::sync::{Arc, Mutex, mpsc};
 use std::thread;
 use std::thread::JoinHandle;
 
 type InternalSignal = (SocketAddr, String);
+type ReceiveHook = Arc<Mutex<Option<Box<dyn Fn() + Send>>>>;
 
 pub struct UdpServer {
   stop_flag: Arc<AtomicBool>,
@@ -18,24 +19,40 @@ pub struct UdpServer {
 
   rx_reader: mpsc::Receiver<InternalSignal>,
   tx_sender: mpsc::Sender<InternalSignal>,
+  on_receive: ReceiveHook,
 
   messages: HashMap<String, VecDeque<(SocketAddr, String)>>,
 }
 
-fn udp_reader(socket: UdpSocket, tx: mpsc::Sender<InternalSignal>, stop_flag: Arc<AtomicBool>) {
+fn udp_reader(
+  socket: UdpSocket,
+  tx: mpsc::Sender<InternalSignal>,
+  on_receive: ReceiveHook,
+  stop_flag: Arc<AtomicBool>,
+) {
   let mut buf = [0; 1024];
   while !stop_flag.load(Ordering::Relaxed) {
     match socket.recv_from(&mut buf) {
       Ok((len, src)) => {
         let msg = String::from_utf8_lossy(&buf[..len]).to_string();
         match tx.send((src, msg)) {
-          Ok(()) => {}
+          Ok(()) => {
+            if let Ok(hook) = on_receive.lock()
+              && let Some(f) = hook.as_ref()
+            {
+              f();
+            }
+          }
           Err(e) => {
             error!("mpsc send error: {e:?}");
           }
         }
       }
-      Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+      Err(ref e)
+        if matches!(
+          e.kind(),
+          std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+        ) => {}
       Err(e) => {
         error!("udp error: {e:?}");
       }
@@ -45,7 +62,7 @@ fn udp_reader(socket: UdpSocket, tx: mpsc::Sender<InternalSignal>, stop_flag: Ar
 
 fn udp_sender(socket: UdpSocket, rx: mpsc::Receiver<InternalSignal>, stop_flag: Arc<AtomicBool>) {
   while !stop_flag.load(Ordering::Relaxed) {
-    match rx.try_recv() {
+    match rx.recv_timeout(POLL_INTERVAL) {
       Ok(v) => {
         let (addr, msg) = v;
         match socket.send_to(msg.as_bytes(), addr) {
@@ -55,26 +72,45 @@ fn udp_sender(socket: UdpSocket, rx: mpsc::Receiver<InternalSignal>, stop_flag:
           }
         }
       }
-      Err(mpsc::TryRecvError::Empty) => {}
-      Err(mpsc::TryRecvError::Disconnected) => {
+      Err(mpsc::RecvTimeoutError::Timeout) => {}
+      Err(mpsc::RecvTimeoutError::Disconnected) => {
         break;
       }
     }
   }
 }
 
+impl UdpServer {
+  /// calls a function from the reader thread whenever a message arrives, to wake up the thread fetching them
+  pub fn on_receive(&self, f: impl Fn() + Send + 'static) {
+    if let Ok(mut hook) = self.on_receive.lock() {
+      *hook = Some(Box::new(f));
+    }
+  }
+}
+
 impl InterServer for UdpServer {
   fn start(addr: &str) -> Result<Self, InterError> {
     let socket = UdpSocket::bind(addr).map_err(InterError::IOError)?;
-    socket.set_nonblocking(true).map_err(InterError::IOError)?;
+    socket
+      .set_read_timeout(Some(POLL_INTERVAL))
+      .map_err(InterError::IOError)?;
 
     let stop_flag = Arc::new(AtomicBool::new(false));
 
     let socket_reader = socket.try_clone().map_err(InterError::IOError)?;
     let (tx_reader, rx_reader) = mpsc::channel::<InternalSignal>();
+    let on_receive: ReceiveHook = Arc::new(Mutex::new(None));
+    let on_receive_reader = Arc::clone(&on_receive);
     let stop_flag_reader = Arc::clone(&stop_flag);
-    let handle_reader =
-      thread::spawn(move || udp_reader(socket_reader, tx_reader, stop_flag_reader));
+    let handle_reader = thread::spawn(move || {
+      udp_reader(
+        socket_reader,
+        tx_reader,
+        on_receive_reader,
+        stop_flag_reader,
+      )
+    });
 
     let socket_sender = socket.try_clone().map_err(InterError::IOError)?;
     let (tx_sender, rx_sender) = mpsc::channel::<InternalSignal>();
@@ -90,6 +126,7 @@ impl InterServer for UdpServer {
 
       rx_reader,
       tx_sender,
+      on_receive,
 
       messages: HashMap::new(),
     };
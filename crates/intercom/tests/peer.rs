@@ -0,0 +1,57 @@
+use intercom::peer::udp::UdpPeer;
+use intercom::peer::{InterPeer, InterPeerCommunicator};
+use intercom::{InterError, InterMessageIncoming, InterMessageOutgoing, InterMessagePrefixed};
+use std::net::Ipv4Addr;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq)]
+enum MessageBetweenPeers {
+  Hi(u8),
+}
+
+impl InterMessagePrefixed for MessageBetweenPeers {
+  fn get_prefix() -> String {
+    String::from("peer")
+  }
+}
+
+impl InterMessageIncoming for MessageBetweenPeers {
+  fn from_raw(raw: Vec<&str>) -> Option<Self> {
+    match raw[..] {
+      ["hi", n] => n.parse().ok().map(Self::Hi),
+      _ => None,
+    }
+  }
+}
+
+impl InterMessageOutgoing for MessageBetweenPeers {
+  fn to_raw(self) -> Result<String, InterError> {
+    match self {
+      Self::Hi(n) => Ok(format!("hi,{n}")),
+    }
+  }
+}
+
+struct PeerCommunicator {}
+impl InterPeerCommunicator<UdpPeer, MessageBetweenPeers, MessageBetweenPeers> for PeerCommunicator {}
+
+#[test]
+fn every_peer_gets_the_message() {
+  let group = "239.255.42.42:21436";
+  let mut a = UdpPeer::join_on(group, Ipv4Addr::LOCALHOST).unwrap();
+  let mut b = UdpPeer::join_on(group, Ipv4Addr::LOCALHOST).unwrap();
+
+  PeerCommunicator::broadcast_message(&a, MessageBetweenPeers::Hi(1)).unwrap();
+  sleep(Duration::from_millis(100));
+
+  for peer in [&mut a, &mut b] {
+    peer.fetch().unwrap();
+    let messages = PeerCommunicator::get_messages(peer).unwrap();
+    let messages = messages.into_iter().map(|(_, m)| m).collect::<Vec<_>>();
+    assert_eq!(messages, vec![MessageBetweenPeers::Hi(1)]);
+  }
+
+  a.leave().unwrap();
+  b.leave().unwrap();
+}
@@ -3,8 +3,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
   data::{
-    buttons::{ActionDescriptor, SongButtonActionValue},
+    buttons::{ActionContext, ActionDescriptor, SongButtonActionValue},
     channels::Channel,
+    references::Reference,
+    validation::Severity,
   },
   messages::renoise::MessageToRenoise,
 };
@@ -27,8 +29,8 @@ impl Default for ParameterValue {
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct CycleEffectParameterValue {
   pub track: Channel,
-  pub effect: u64,
-  pub param: u64,
+  pub effect: Reference,
+  pub param: Reference,
   pub default: usize,
   pub cycles: Vec<ParameterValue>,
 }
@@ -49,8 +51,8 @@ impl ActionDescriptor for CycleEffectParameterValue {
   }
 
   fn get_default_color(&self) -> [u8; 3] {
-    // TODO: if there is an error here, it means the set is malformed!
-    self.color_from_usize(self.default).unwrap()
+    // malformed sets are reported by Set::validate
+    self.color_from_usize(self.default).unwrap_or([0, 0, 0])
   }
 
   fn get_color(&self, value: SongButtonActionValue) -> Result<[u8; 3]> {
@@ -77,6 +79,7 @@ impl ActionDescriptor for CycleEffectParameterValue {
   fn create_renoise_message(
     &self,
     value: SongButtonActionValue,
+    ctx: &ActionContext,
   ) -> Result<Vec<crate::messages::renoise::MessageToRenoise>> {
     match value {
       SongButtonActionValue::Number(n) => {
@@ -84,14 +87,81 @@ impl ActionDescriptor for CycleEffectParameterValue {
           .cycles
           .get(n)
           .ok_or(anyhow::Error::msg("no such cycle"))?;
+        let track = ctx.layout.resolve(&self.track)?;
+        let effect = ctx.resolve_device(track, &self.effect)?;
         Ok(vec![MessageToRenoise::SetParameterValue(
-          self.track.to_renoise_number(),
-          self.effect,
-          self.param,
+          track,
+          effect,
+          ctx.resolve_parameter(track, effect, &self.param)?,
           c.value,
         )])
       }
       _ => Err(anyhow::Error::msg("invalid value")),
     }
   }
+
+  fn morph_renoise_message(
+    &self,
+    from: SongButtonActionValue,
+    to: SongButtonActionValue,
+    amount: f64,
+    ctx: &ActionContext,
+  ) -> Result<Vec<MessageToRenoise>> {
+    match (from, to) {
+      (SongButtonActionValue::Number(a), SongButtonActionValue::Number(b)) if a != b => {
+        let a = self
+          .cycles
+          .get(a)
+          .ok_or(anyhow::Error::msg("no such cycle"))?;
+        let b = self
+          .cycles
+          .get(b)
+          .ok_or(anyhow::Error::msg("no such cycle"))?;
+        let track = ctx.layout.resolve(&self.track)?;
+        let effect = ctx.resolve_device(track, &self.effect)?;
+        Ok(vec![MessageToRenoise::SetParameterValue(
+          track,
+          effect,
+          ctx.resolve_parameter(track, effect, &self.param)?,
+          a.value + (b.value - a.value) * amount.clamp(0., 1.),
+        )])
+      }
+      (SongButtonActionValue::Number(_), SongButtonActionValue::Number(_)) => Ok(Vec::new()),
+      _ => Err(anyhow::Error::msg("invalid value")),
+    }
+  }
+
+  fn validate(&self, ctx: &ActionContext) -> Vec<(Severity, String)> {
+    let mut issues = Vec::new();
+    if let Err(e) = ctx.layout.resolve(&self.track) {
+      issues.push((Severity::Error, e.to_string()));
+    }
+    if self.cycles.is_empty() {
+      issues.push((Severity::Error, String::from("no cycles")));
+    } else if self.default >= self.cycles.len() {
+      issues.push((
+        Severity::Error,
+        format!(
+          "default cycle {} is out of range, there are {} cycles",
+          self.default,
+          self.cycles.len()
+        ),
+      ));
+    }
+    issues
+  }
+
+  fn unresolved_references(&self, ctx: &ActionContext) -> Vec<String> {
+    // unknown channels are reported by validate
+    let Ok(track) = ctx.layout.resolve(&self.track) else {
+      return Vec::new();
+    };
+    ctx
+      .resolve_device(track, &self.effect)
+      .and_then(|effect| ctx.resolve_parameter(track, effect, &self.param))
+      .err()
+      .map(|e| e.to_string())
+      .into_iter()
+      .collect()
+  }
 }
@@ -0,0 +1,173 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::{
+  data::{
+    buttons::{ActionContext, ActionDescriptor, SongButtonAction, SongButtonActionValue},
+    validation::Severity,
+  },
+  messages::renoise::MessageToRenoise,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct MacroStep {
+  pub action: SongButtonAction,
+  /// in milliseconds, counted from when the macro fires
+  pub delay: u64,
+}
+
+/// toggles a list of actions together
+///
+/// when on, every step is one press away from its default; when off, every step is back to its default
+///
+/// stateless steps fire when the macro switches on only
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Macro {
+  pub steps: Vec<MacroStep>,
+  pub default: bool,
+  pub color_off: [u8; 3],
+  pub color_on: [u8; 3],
+}
+
+impl Macro {
+  fn color_from_bool(&self, b: bool) -> [u8; 3] {
+    if b { self.color_on } else { self.color_off }
+  }
+
+  /// steps that only fire, they have no state to go back to
+  fn is_stateless(step: &MacroStep) -> bool {
+    step.action.get_default() == SongButtonActionValue::None
+  }
+
+  fn step_value(step: &MacroStep, b: bool) -> Result<SongButtonActionValue> {
+    let default = step.action.get_default();
+    if b {
+      step.action.next(default)
+    } else {
+      Ok(default)
+    }
+  }
+}
+
+impl ActionDescriptor for Macro {
+  fn get_default(&self) -> SongButtonActionValue {
+    SongButtonActionValue::Boolean(self.default)
+  }
+
+  fn get_default_color(&self) -> [u8; 3] {
+    self.color_from_bool(self.default)
+  }
+
+  fn get_color(&self, value: SongButtonActionValue) -> Result<[u8; 3]> {
+    match value {
+      SongButtonActionValue::Boolean(b) => Ok(self.color_from_bool(b)),
+      _ => Err(anyhow::Error::msg("invalid value")),
+    }
+  }
+
+  fn next(&self, value: SongButtonActionValue) -> Result<SongButtonActionValue> {
+    match value {
+      SongButtonActionValue::Boolean(b) => Ok(SongButtonActionValue::Boolean(!b)),
+      _ => Err(anyhow::Error::msg("invalid value")),
+    }
+  }
+
+  fn create_renoise_message(
+    &self,
+    value: SongButtonActionValue,
+    ctx: &ActionContext,
+  ) -> Result<Vec<MessageToRenoise>> {
+    Ok(
+      self
+        .create_timed_renoise_message(value, ctx)?
+        .into_iter()
+        .map(|(_, m)| m)
+        .collect(),
+    )
+  }
+
+  fn create_timed_renoise_message(
+    &self,
+    value: SongButtonActionValue,
+    ctx: &ActionContext,
+  ) -> Result<Vec<(Duration, MessageToRenoise)>> {
+    match value {
+      SongButtonActionValue::Boolean(b) => {
+        let mut msgs = Vec::new();
+        for step in &self.steps {
+          if !b && Macro::is_stateless(step) {
+            continue;
+          }
+          let delay = Duration::from_millis(step.delay);
+          let step_msgs = step
+            .action
+            .create_timed_renoise_message(Macro::step_value(step, b)?, ctx)?;
+          for (d, m) in step_msgs {
+            msgs.push((delay + d, m));
+          }
+        }
+        Ok(msgs)
+      }
+      _ => Err(anyhow::Error::msg("invalid value")),
+    }
+  }
+
+  fn create_restore_renoise_message(
+    &self,
+    value: SongButtonActionValue,
+    ctx: &ActionContext,
+  ) -> Result<Vec<(Duration, MessageToRenoise)>> {
+    match value {
+      SongButtonActionValue::Boolean(b) => {
+        let mut msgs = Vec::new();
+        for step in &self.steps {
+          let delay = Duration::from_millis(step.delay);
+          let step_msgs = step
+            .action
+            .create_restore_renoise_message(Macro::step_value(step, b)?, ctx)?;
+          for (d, m) in step_msgs {
+            msgs.push((delay + d, m));
+          }
+        }
+        Ok(msgs)
+      }
+      _ => Err(anyhow::Error::msg("invalid value")),
+    }
+  }
+
+  fn validate(&self, ctx: &ActionContext) -> Vec<(Severity, String)> {
+    let mut issues = Vec::new();
+    if self.steps.is_empty() {
+      issues.push((Severity::Warning, String::from("no steps")));
+    }
+    for (i, step) in self.steps.iter().enumerate() {
+      match step.action {
+        SongButtonAction::PlaySample(_) => issues.push((
+          Severity::Error,
+          format!("step {}: PlaySample isn't implemented yet", i),
+        )),
+        // scripts only run as buttons
+        SongButtonAction::Script(_) => issues.push((
+          Severity::Error,
+          format!("step {}: scripts can't be macro steps", i),
+        )),
+        _ => {}
+      }
+      for (severity, message) in step.action.validate(ctx) {
+        issues.push((severity, format!("step {}: {}", i, message)));
+      }
+    }
+    issues
+  }
+
+  fn unresolved_references(&self, ctx: &ActionContext) -> Vec<String> {
+    let mut unresolved = Vec::new();
+    for (i, step) in self.steps.iter().enumerate() {
+      for message in step.action.unresolved_references(ctx) {
+        unresolved.push(format!("step {}: {}", i, message));
+      }
+    }
+    unresolved
+  }
+}
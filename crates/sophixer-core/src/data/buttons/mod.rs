@@ -1,17 +1,59 @@
-use std::fmt::Display;
+use std::{fmt::Display, time::Duration};
 
 use anyhow::Result;
 use enum_dispatch::enum_dispatch;
 use serde::{Deserialize, Serialize};
 
-use crate::messages::renoise::MessageToRenoise;
+use crate::{
+  data::{
+    layout::TrackLayout,
+    quantization::Quantization,
+    references::{DeviceChains, Reference},
+    validation::Severity,
+  },
+  messages::renoise::MessageToRenoise,
+};
 
 pub mod cycle_effect_parameter_value;
+pub mod macros;
 pub mod play_sample;
+pub mod play_section;
+pub mod script;
 pub mod toggle_channels;
 pub mod toggle_effect_bypass;
 pub mod toggle_track_patterns;
 
+/// what actions need to know about the renoise project
+pub struct ActionContext<'a> {
+  pub layout: &'a TrackLayout,
+  /// only known once Calcium reported them
+  pub devices: Option<&'a DeviceChains>,
+}
+
+impl ActionContext<'_> {
+  pub fn resolve_device(&self, track: u64, device: &Reference) -> Result<u64> {
+    match (device, self.devices) {
+      (Reference::Index(i), _) => Ok(*i),
+      (_, Some(devices)) => devices.resolve_device(track, device),
+      (_, None) => Err(anyhow::Error::msg(format!(
+        "can't resolve device {} before renoise reports its devices",
+        device
+      ))),
+    }
+  }
+
+  pub fn resolve_parameter(&self, track: u64, device: u64, parameter: &Reference) -> Result<u64> {
+    match (parameter, self.devices) {
+      (Reference::Index(i), _) => Ok(*i),
+      (_, Some(devices)) => devices.resolve_parameter(track, device, parameter),
+      (_, None) => Err(anyhow::Error::msg(format!(
+        "can't resolve parameter {} before renoise reports its devices",
+        parameter
+      ))),
+    }
+  }
+}
+
 #[enum_dispatch]
 pub trait ActionDescriptor {
   fn get_default(&self) -> SongButtonActionValue;
@@ -21,7 +63,83 @@ pub trait ActionDescriptor {
 
   fn next(&self, value: SongButtonActionValue) -> Result<SongButtonActionValue>;
 
-  fn create_renoise_message(&self, value: SongButtonActionValue) -> Result<Vec<MessageToRenoise>>;
+  /// whether a value counts as switched on, for exclusive groups
+  ///
+  /// booleans are on when true, numbers past their first value, which is the off one
+  fn is_active(&self, value: SongButtonActionValue) -> bool {
+    match value {
+      SongButtonActionValue::None => false,
+      SongButtonActionValue::Boolean(b) => b,
+      SongButtonActionValue::Number(n) => n != 0,
+    }
+  }
+
+  /// value taken when another member of an exclusive group gets switched on, see [`Self::is_active`]
+  fn get_inactive(&self) -> SongButtonActionValue {
+    match self.get_default() {
+      SongButtonActionValue::None => SongButtonActionValue::None,
+      SongButtonActionValue::Boolean(_) => SongButtonActionValue::Boolean(false),
+      SongButtonActionValue::Number(_) => SongButtonActionValue::Number(0),
+    }
+  }
+
+  fn create_renoise_message(
+    &self,
+    value: SongButtonActionValue,
+    ctx: &ActionContext,
+  ) -> Result<Vec<MessageToRenoise>>;
+
+  /// problems with the action itself, see [`crate::data::Set::validate`]
+  fn validate(&self, _ctx: &ActionContext) -> Vec<(Severity, String)> {
+    Vec::new()
+  }
+
+  /// named references that don't resolve against `ctx.devices`
+  fn unresolved_references(&self, _ctx: &ActionContext) -> Vec<String> {
+    Vec::new()
+  }
+
+  /// messages for a point between two values, amount going from 0 (from) to 1 (to)
+  ///
+  /// only continuous actions morph, the others send nothing
+  fn morph_renoise_message(
+    &self,
+    _from: SongButtonActionValue,
+    _to: SongButtonActionValue,
+    _amount: f64,
+    _ctx: &ActionContext,
+  ) -> Result<Vec<MessageToRenoise>> {
+    Ok(Vec::new())
+  }
+
+  /// messages along with how long to wait before sending them
+  fn create_timed_renoise_message(
+    &self,
+    value: SongButtonActionValue,
+    ctx: &ActionContext,
+  ) -> Result<Vec<(Duration, MessageToRenoise)>> {
+    Ok(
+      self
+        .create_renoise_message(value, ctx)?
+        .into_iter()
+        .map(|m| (Duration::ZERO, m))
+        .collect(),
+    )
+  }
+
+  /// timed messages that bring renoise back to a value without firing anything
+  ///
+  /// stateless actions, whose default is [`SongButtonActionValue::None`], send nothing
+  fn create_restore_renoise_message(
+    &self,
+    value: SongButtonActionValue,
+    ctx: &ActionContext,
+  ) -> Result<Vec<(Duration, MessageToRenoise)>> {
+    if self.get_default() == SongButtonActionValue::None {
+      return Ok(Vec::new());
+    }
+    self.create_timed_renoise_message(value, ctx)
+  }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -32,6 +150,9 @@ pub enum SongButtonAction {
   ToggleEffectBypass(toggle_effect_bypass::ToggleEffectBypass),
   CycleEffectParameterValue(cycle_effect_parameter_value::CycleEffectParameterValue),
   PlaySample(play_sample::PlaySample),
+  PlaySection(play_section::PlaySection),
+  Macro(macros::Macro),
+  Script(script::Script),
 }
 
 impl Display for SongButtonAction {
@@ -42,6 +163,9 @@ impl Display for SongButtonAction {
       Self::ToggleEffectBypass(_) => write!(f, "ToggleEffectBypass"),
       Self::CycleEffectParameterValue(_) => write!(f, "CycleEffectParameterValue"),
       Self::PlaySample(_) => write!(f, "PlaySample"),
+      Self::PlaySection(_) => write!(f, "PlaySection"),
+      Self::Macro(_) => write!(f, "Macro"),
+      Self::Script(_) => write!(f, "Script"),
     }
   }
 }
@@ -52,7 +176,7 @@ impl Default for SongButtonAction {
   }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SongButtonActionValue {
   None,
   Boolean(bool),
@@ -62,10 +186,15 @@ pub enum SongButtonActionValue {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SongButton {
   pub action: SongButtonAction,
+  #[serde(default)]
+  pub quantization: Quantization,
 }
 
 impl SongButton {
   pub fn new(action: SongButtonAction) -> Result<SongButton> {
-    Ok(Self { action })
+    Ok(Self {
+      action,
+      quantization: Quantization::Off,
+    })
   }
 }
@@ -2,8 +2,9 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
 use crate::data::{
-  buttons::{ActionDescriptor, SongButtonActionValue},
+  buttons::{ActionContext, ActionDescriptor, SongButtonActionValue},
   channels::Channel,
+  validation::Severity,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
@@ -41,6 +42,7 @@ impl ActionDescriptor for PlaySample {
   fn create_renoise_message(
     &self,
     value: SongButtonActionValue,
+    _ctx: &ActionContext,
   ) -> Result<Vec<crate::messages::renoise::MessageToRenoise>> {
     match value {
       SongButtonActionValue::None => {
@@ -49,4 +51,11 @@ impl ActionDescriptor for PlaySample {
       _ => Err(anyhow::Error::msg("invalid value")),
     }
   }
+
+  fn validate(&self, ctx: &ActionContext) -> Vec<(Severity, String)> {
+    match ctx.layout.resolve(&self.track) {
+      Ok(_) => Vec::new(),
+      Err(e) => vec![(Severity::Error, e.to_string())],
+    }
+  }
 }
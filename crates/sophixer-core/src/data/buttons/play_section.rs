@@ -0,0 +1,72 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  data::{
+    buttons::{ActionContext, ActionDescriptor, SongButtonActionValue},
+    validation::Severity,
+  },
+  messages::renoise::MessageToRenoise,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlaySection {
+  pub start: u64,
+  pub loop_start: u64,
+  pub loop_end: u64,
+  /// jump right away instead of waiting for the end of the pattern
+  pub force: bool,
+  pub color: [u8; 3],
+}
+
+impl ActionDescriptor for PlaySection {
+  fn get_default(&self) -> SongButtonActionValue {
+    SongButtonActionValue::None
+  }
+
+  fn get_default_color(&self) -> [u8; 3] {
+    self.color
+  }
+
+  fn get_color(&self, value: SongButtonActionValue) -> Result<[u8; 3]> {
+    match value {
+      SongButtonActionValue::None => Ok(self.color),
+      _ => Err(anyhow::Error::msg("invalid value")),
+    }
+  }
+
+  fn next(&self, value: SongButtonActionValue) -> Result<SongButtonActionValue> {
+    match value {
+      SongButtonActionValue::None => Ok(SongButtonActionValue::None),
+      _ => Err(anyhow::Error::msg("invalid value")),
+    }
+  }
+
+  fn create_renoise_message(
+    &self,
+    value: SongButtonActionValue,
+    _ctx: &ActionContext,
+  ) -> Result<Vec<MessageToRenoise>> {
+    match value {
+      SongButtonActionValue::None => Ok(vec![
+        MessageToRenoise::PlaySection(self.start, self.force),
+        MessageToRenoise::SetLoop(self.loop_start, self.loop_end),
+      ]),
+      _ => Err(anyhow::Error::msg("invalid value")),
+    }
+  }
+
+  fn validate(&self, _ctx: &ActionContext) -> Vec<(Severity, String)> {
+    if self.loop_start > self.loop_end {
+      vec![(
+        Severity::Error,
+        format!(
+          "loop starts at {} after its end at {}",
+          self.loop_start, self.loop_end
+        ),
+      )]
+    } else {
+      Vec::new()
+    }
+  }
+}
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  data::{
+    buttons::{ActionContext, ActionDescriptor, SongButtonActionValue},
+    validation::Severity,
+  },
+  messages::renoise::MessageToRenoise,
+};
+
+/// runs a rhai script, which sends its own messages
+///
+/// scripts are run by tin, which keeps their state and lights, so the action itself sends nothing
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script {
+  /// relative to the set file
+  pub file: String,
+  /// until the script lights the button itself
+  pub color: [u8; 3],
+}
+
+impl ActionDescriptor for Script {
+  fn get_default(&self) -> SongButtonActionValue {
+    SongButtonActionValue::None
+  }
+
+  fn get_default_color(&self) -> [u8; 3] {
+    self.color
+  }
+
+  fn get_color(&self, value: SongButtonActionValue) -> Result<[u8; 3]> {
+    match value {
+      SongButtonActionValue::None => Ok(self.color),
+      _ => Err(anyhow::Error::msg("invalid value")),
+    }
+  }
+
+  fn next(&self, value: SongButtonActionValue) -> Result<SongButtonActionValue> {
+    match value {
+      SongButtonActionValue::None => Ok(SongButtonActionValue::None),
+      _ => Err(anyhow::Error::msg("invalid value")),
+    }
+  }
+
+  fn create_renoise_message(
+    &self,
+    value: SongButtonActionValue,
+    _ctx: &ActionContext,
+  ) -> Result<Vec<MessageToRenoise>> {
+    match value {
+      SongButtonActionValue::None => Ok(Vec::new()),
+      _ => Err(anyhow::Error::msg("invalid value")),
+    }
+  }
+
+  fn validate(&self, _ctx: &ActionContext) -> Vec<(Severity, String)> {
+    if self.file.is_empty() {
+      vec![(Severity::Error, String::from("no script file"))]
+    } else {
+      Vec::new()
+    }
+  }
+}
@@ -4,8 +4,9 @@ use std::collections::HashSet;
 
 use crate::{
   data::{
-    buttons::{ActionDescriptor, SongButtonActionValue},
+    buttons::{ActionContext, ActionDescriptor, SongButtonActionValue},
     channels::Channel,
+    validation::Severity,
   },
   messages::renoise::MessageToRenoise,
 };
@@ -50,16 +51,30 @@ impl ActionDescriptor for ToggleChannels {
   fn create_renoise_message(
     &self,
     value: SongButtonActionValue,
+    ctx: &ActionContext,
   ) -> Result<Vec<crate::messages::renoise::MessageToRenoise>> {
     match value {
       SongButtonActionValue::Boolean(b) => {
         let mut msgs = Vec::new();
         for c in &self.channels {
-          msgs.push(MessageToRenoise::MuteTrack(c.to_renoise_number(), !b));
+          msgs.push(MessageToRenoise::MuteTrack(ctx.layout.resolve(c)?, !b));
         }
         Ok(msgs)
       }
       _ => Err(anyhow::Error::msg("invalid value")),
     }
   }
+
+  fn validate(&self, ctx: &ActionContext) -> Vec<(Severity, String)> {
+    let mut issues = Vec::new();
+    if self.channels.is_empty() {
+      issues.push((Severity::Warning, String::from("no channels to toggle")));
+    }
+    for c in &self.channels {
+      if let Err(e) = ctx.layout.resolve(c) {
+        issues.push((Severity::Error, e.to_string()));
+      }
+    }
+    issues
+  }
 }
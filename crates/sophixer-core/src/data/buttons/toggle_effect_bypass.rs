@@ -3,8 +3,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
   data::{
-    buttons::{ActionDescriptor, SongButtonActionValue},
+    buttons::{ActionContext, ActionDescriptor, SongButtonActionValue},
     channels::Channel,
+    references::Reference,
+    validation::Severity,
   },
   messages::renoise::MessageToRenoise,
 };
@@ -12,7 +14,7 @@ use crate::{
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct ToggleEffectBypass {
   pub track: Channel,
-  pub effect: u64,
+  pub effect: Reference,
   pub default: bool,
   pub color_off: [u8; 3],
   pub color_on: [u8; 3],
@@ -50,14 +52,38 @@ impl ActionDescriptor for ToggleEffectBypass {
   fn create_renoise_message(
     &self,
     value: SongButtonActionValue,
+    ctx: &ActionContext,
   ) -> Result<Vec<crate::messages::renoise::MessageToRenoise>> {
     match value {
-      SongButtonActionValue::Boolean(b) => Ok(vec![MessageToRenoise::BypassEffect(
-        self.track.to_renoise_number(),
-        self.effect,
-        !b,
-      )]),
+      SongButtonActionValue::Boolean(b) => {
+        let track = ctx.layout.resolve(&self.track)?;
+        Ok(vec![MessageToRenoise::BypassEffect(
+          track,
+          ctx.resolve_device(track, &self.effect)?,
+          !b,
+        )])
+      }
       _ => Err(anyhow::Error::msg("invalid value")),
     }
   }
+
+  fn validate(&self, ctx: &ActionContext) -> Vec<(Severity, String)> {
+    match ctx.layout.resolve(&self.track) {
+      Ok(_) => Vec::new(),
+      Err(e) => vec![(Severity::Error, e.to_string())],
+    }
+  }
+
+  fn unresolved_references(&self, ctx: &ActionContext) -> Vec<String> {
+    // unknown channels are reported by validate
+    let Ok(track) = ctx.layout.resolve(&self.track) else {
+      return Vec::new();
+    };
+    ctx
+      .resolve_device(track, &self.effect)
+      .err()
+      .map(|e| e.to_string())
+      .into_iter()
+      .collect()
+  }
 }
@@ -4,8 +4,9 @@ use std::collections::HashSet;
 
 use crate::{
   data::{
-    buttons::{ActionDescriptor, SongButtonActionValue},
+    buttons::{ActionContext, ActionDescriptor, SongButtonActionValue},
     channels::Channel,
+    validation::Severity,
   },
   messages::renoise::MessageToRenoise,
 };
@@ -50,13 +51,14 @@ impl ActionDescriptor for ToggleTrackPatterns {
   fn create_renoise_message(
     &self,
     value: SongButtonActionValue,
+    ctx: &ActionContext,
   ) -> Result<Vec<crate::messages::renoise::MessageToRenoise>> {
     match value {
       SongButtonActionValue::Boolean(b) => {
         let mut msgs = Vec::new();
         for (c, seq) in &self.track_patterns {
           msgs.push(MessageToRenoise::MuteTrackSequenceSlot(
-            c.to_renoise_number(),
+            ctx.layout.resolve(c)?,
             *seq,
             !b,
           ));
@@ -66,4 +68,20 @@ impl ActionDescriptor for ToggleTrackPatterns {
       _ => Err(anyhow::Error::msg("invalid value")),
     }
   }
+
+  fn validate(&self, ctx: &ActionContext) -> Vec<(Severity, String)> {
+    let mut issues = Vec::new();
+    if self.track_patterns.is_empty() {
+      issues.push((
+        Severity::Warning,
+        String::from("no track patterns to toggle"),
+      ));
+    }
+    for (c, _) in &self.track_patterns {
+      if let Err(e) = ctx.layout.resolve(c) {
+        issues.push((Severity::Error, e.to_string()));
+      }
+    }
+    issues
+  }
 }
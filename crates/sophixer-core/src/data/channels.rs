@@ -2,29 +2,45 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Ord)]
+/// a track of the renoise project, resolved through the set's [`crate::data::layout::TrackLayout`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[serde(from = "ChannelRepr")]
 pub enum Channel {
+  /// n-th track of a group, starting from 1
+  Track(String, u64),
+  GroupMaster(String),
+  Send(String),
+  #[default]
+  Master,
+}
+
+/// channels as they can be found in set files
+///
+/// sets before version 2 used fixed channels, which are the groups of the default layout
+#[derive(Deserialize)]
+#[serde(rename = "Channel")]
+enum ChannelRepr {
+  Track(String, u64),
+  GroupMaster(String),
+  Send(String),
+  Master,
   Lead(u64),
   Drum(u64),
   MasterLead,
   MasterDrum,
-  Master,
-}
-
-impl Default for Channel {
-  fn default() -> Self {
-    Self::Master
-  }
 }
 
-impl Channel {
-  pub fn to_renoise_number(&self) -> u64 {
-    match self {
-      Self::Lead(n) => *n,
-      Self::Drum(n) => 7 + *n,
-      Self::MasterLead => 7,
-      Self::MasterDrum => 14,
-      Self::Master => 15,
+impl From<ChannelRepr> for Channel {
+  fn from(value: ChannelRepr) -> Self {
+    match value {
+      ChannelRepr::Track(group, n) => Self::Track(group, n),
+      ChannelRepr::GroupMaster(group) => Self::GroupMaster(group),
+      ChannelRepr::Send(name) => Self::Send(name),
+      ChannelRepr::Master => Self::Master,
+      ChannelRepr::Lead(n) => Self::Track(String::from("lead"), n),
+      ChannelRepr::Drum(n) => Self::Track(String::from("drum"), n),
+      ChannelRepr::MasterLead => Self::GroupMaster(String::from("lead")),
+      ChannelRepr::MasterDrum => Self::GroupMaster(String::from("drum")),
     }
   }
 }
@@ -32,17 +48,10 @@ impl Channel {
 impl Display for Channel {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
-      Self::Lead(n) => write!(f, "Lead{}", n),
-      Self::Drum(n) => write!(f, "Drum{}", n),
-      Self::MasterLead => write!(f, "MasterLead"),
-      Self::MasterDrum => write!(f, "MasterDrum"),
+      Self::Track(group, n) => write!(f, "{}{}", group, n),
+      Self::GroupMaster(group) => write!(f, "{} master", group),
+      Self::Send(name) => write!(f, "send {}", name),
       Self::Master => write!(f, "Master"),
     }
   }
 }
-
-impl PartialOrd for Channel {
-  fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-    Some(self.to_renoise_number().cmp(&other.to_renoise_number()))
-  }
-}
@@ -0,0 +1,40 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// what a pattern launches once it looped enough, see [`crate::data::SongPattern::loops`]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub enum FollowAction {
+  #[default]
+  None,
+  /// the pattern at this y
+  Goto(i64),
+  /// the pattern launched before this one
+  Back,
+  /// stops the transport
+  Stop,
+  /// one of these patterns, at random
+  Random(Vec<i64>),
+  /// the next song of the setlist, see [`crate::data::Set::get_setlist`]
+  NextSong,
+}
+
+impl Display for FollowAction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::None => write!(f, "None"),
+      Self::Goto(y) => write!(f, "Goto {}", y),
+      Self::Back => write!(f, "Back"),
+      Self::Stop => write!(f, "Stop"),
+      Self::NextSong => write!(f, "NextSong"),
+      Self::Random(ys) => write!(
+        f,
+        "Random {}",
+        ys.iter()
+          .map(|y| y.to_string())
+          .collect::<Vec<String>>()
+          .join(", ")
+      ),
+    }
+  }
+}
@@ -0,0 +1,107 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::data::channels::Channel;
+
+/// a named group of tracks, like the leads or the drums
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TrackGroup {
+  pub name: String,
+  /// renoise indexes, `Channel::Track(name, n)` is the n-th one starting from 1
+  pub tracks: Vec<u64>,
+  /// renoise index of the group's master track
+  pub master: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TrackSend {
+  pub name: String,
+  /// renoise index of the send track
+  pub track: u64,
+}
+
+/// how the channels of a set map to the tracks of the renoise project
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TrackLayout {
+  pub groups: Vec<TrackGroup>,
+  pub sends: Vec<TrackSend>,
+  /// renoise index of the master track
+  pub master: u64,
+}
+
+impl Default for TrackLayout {
+  /// the project template from SETUP.md
+  fn default() -> Self {
+    Self {
+      groups: vec![
+        TrackGroup {
+          name: String::from("lead"),
+          tracks: (1..=6).collect(),
+          master: Some(7),
+        },
+        TrackGroup {
+          name: String::from("drum"),
+          tracks: (8..=13).collect(),
+          master: Some(14),
+        },
+      ],
+      sends: Vec::new(),
+      master: 15,
+    }
+  }
+}
+
+impl TrackLayout {
+  pub fn get_group(&self, name: &str) -> Option<&TrackGroup> {
+    self.groups.iter().find(|g| g.name == name)
+  }
+
+  /// renoise index of a channel
+  pub fn resolve(&self, channel: &Channel) -> Result<u64> {
+    match channel {
+      Channel::Track(group, n) => self
+        .get_group(group)
+        .ok_or(anyhow::Error::msg(format!("unknown track group {}", group)))?
+        .tracks
+        .get(n.checked_sub(1).unwrap_or(u64::MAX) as usize)
+        .copied()
+        .ok_or(anyhow::Error::msg(format!(
+          "unknown channel {}, group {} has no track {}",
+          channel, group, n
+        ))),
+      Channel::GroupMaster(group) => self
+        .get_group(group)
+        .ok_or(anyhow::Error::msg(format!("unknown track group {}", group)))?
+        .master
+        .ok_or(anyhow::Error::msg(format!(
+          "track group {} has no master track",
+          group
+        ))),
+      Channel::Send(name) => self
+        .sends
+        .iter()
+        .find(|s| s.name == *name)
+        .map(|s| s.track)
+        .ok_or(anyhow::Error::msg(format!("unknown send {}", name))),
+      Channel::Master => Ok(self.master),
+    }
+  }
+
+  /// every channel of the layout, in layout order
+  pub fn channels(&self) -> Vec<Channel> {
+    let mut channels = Vec::new();
+    for group in &self.groups {
+      for n in 1..=group.tracks.len() {
+        channels.push(Channel::Track(group.name.clone(), n as u64));
+      }
+      if group.master.is_some() {
+        channels.push(Channel::GroupMaster(group.name.clone()));
+      }
+    }
+    for send in &self.sends {
+      channels.push(Channel::Send(send.name.clone()));
+    }
+    channels.push(Channel::Master);
+    channels
+  }
+}
@@ -0,0 +1,63 @@
+//! upgrades of older set files
+//!
+//! every step turns a set file of version `n` into a set file of version `n + 1`,
+//! so old sets go through every step in order until they reach [`CURRENT_VERSION`]
+//!
+//! steps read and write their own copies of the set as it was at their versions, never the current one,
+//! so their output stays the same as the set changes. songs are carried over as they were written
+//!
+//! fields added with a default don't need a new version, older sets read as they are
+
+use anyhow::Result;
+use serde::Deserialize;
+
+mod v0;
+mod v1;
+
+/// version of the set files written by this build
+pub const CURRENT_VERSION: u64 = 2;
+
+#[derive(Deserialize)]
+#[serde(rename = "Set")]
+struct VersionProbe {
+  /// sets written before versioning have no version field
+  #[serde(default)]
+  version: u64,
+}
+
+/// reads the version of a set file without reading the rest of it
+pub fn read_version(input: &str) -> Result<u64> {
+  let probe: VersionProbe = ron::from_str(input)?;
+  Ok(probe.version)
+}
+
+/// upgrades a set file to the current version
+pub fn migrate(input: &str) -> Result<String> {
+  let mut version = read_version(input)?;
+  if version > CURRENT_VERSION {
+    return Err(anyhow::Error::msg(format!(
+      "set file version {} is newer than the supported version {}",
+      version, CURRENT_VERSION
+    )));
+  }
+
+  let mut current = input.to_string();
+  while version < CURRENT_VERSION {
+    current = step(version, &current)?;
+    version += 1;
+  }
+
+  Ok(current)
+}
+
+/// upgrades a set file of the given version by a single version
+pub fn step(version: u64, input: &str) -> Result<String> {
+  match version {
+    0 => v0::migrate(input),
+    1 => v1::migrate(input),
+    _ => Err(anyhow::Error::msg(format!(
+      "no migration from set file version {}",
+      version
+    ))),
+  }
+}
@@ -0,0 +1,27 @@
+//! version 0: sets from before versioning
+
+use anyhow::Result;
+use ron::value::RawValue;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+struct Set {
+  name: String,
+  authors: String,
+  stop_seq_pos: u64,
+  songs: HashMap<String, Box<RawValue>>,
+}
+
+/// only adds the version field
+pub(super) fn migrate(input: &str) -> Result<String> {
+  let old: Set = ron::from_str(input)?;
+  let new = super::v1::Set {
+    version: 1,
+    name: old.name,
+    authors: old.authors,
+    stop_seq_pos: old.stop_seq_pos,
+    songs: old.songs,
+  };
+  Ok(ron::to_string(&new)?)
+}
@@ -0,0 +1,72 @@
+//! version 1: sets with the lead/drum channel template built in
+
+use anyhow::Result;
+use ron::value::RawValue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Set {
+  pub version: u64,
+  pub name: String,
+  pub authors: String,
+  pub stop_seq_pos: u64,
+  pub songs: HashMap<String, Box<RawValue>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "Set")]
+struct SetV2 {
+  version: u64,
+  name: String,
+  authors: String,
+  stop_seq_pos: u64,
+  songs: HashMap<String, Box<RawValue>>,
+  layout: TrackLayout,
+}
+
+#[derive(Serialize)]
+struct TrackLayout {
+  groups: Vec<TrackGroup>,
+  /// the template has no sends
+  sends: Vec<()>,
+  master: u64,
+}
+
+#[derive(Serialize)]
+struct TrackGroup {
+  name: &'static str,
+  tracks: Vec<u64>,
+  master: Option<u64>,
+}
+
+/// adds the track layout of the old template, the one from SETUP.md
+///
+/// channels are read through `Channel`'s legacy names, so songs don't need rewriting
+pub(super) fn migrate(input: &str) -> Result<String> {
+  let old: Set = ron::from_str(input)?;
+  let new = SetV2 {
+    version: 2,
+    name: old.name,
+    authors: old.authors,
+    stop_seq_pos: old.stop_seq_pos,
+    songs: old.songs,
+    layout: TrackLayout {
+      groups: vec![
+        TrackGroup {
+          name: "lead",
+          tracks: (1..=6).collect(),
+          master: Some(7),
+        },
+        TrackGroup {
+          name: "drum",
+          tracks: (8..=13).collect(),
+          master: Some(14),
+        },
+      ],
+      sends: Vec::new(),
+      master: 15,
+    },
+  };
+  Ok(ron::to_string(&new)?)
+}
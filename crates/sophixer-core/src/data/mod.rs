@@ -1,18 +1,105 @@
 pub mod buttons;
 pub mod channels;
+pub mod follow;
+pub mod layout;
+pub mod migrations;
+pub mod quantization;
+pub mod references;
+pub mod validation;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::data::buttons::SongButton;
+use crate::{
+  data::{
+    buttons::{SongButton, SongButtonActionValue},
+    follow::FollowAction,
+    layout::TrackLayout,
+    quantization::Quantization,
+  },
+  messages::renoise::Transport,
+};
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SongPattern {
   pub start: u64,
   pub loop_start: u64,
   pub loop_end: u64,
   pub color: [u8; 3],
+  /// `Pattern` leaves scheduling to renoise, `Off` jumps right away
+  #[serde(default = "default_pattern_quantization")]
+  pub quantization: Quantization,
+  /// loops played before the follow action, 0 loops forever
+  #[serde(default)]
+  pub loops: u64,
+  #[serde(default)]
+  pub follow: FollowAction,
+}
+
+fn default_pattern_quantization() -> Quantization {
+  Quantization::Pattern
+}
+
+impl Default for SongPattern {
+  fn default() -> Self {
+    Self {
+      start: 0,
+      loop_start: 0,
+      loop_end: 0,
+      color: [0, 0, 0],
+      quantization: default_pattern_quantization(),
+      loops: 0,
+      follow: FollowAction::None,
+    }
+  }
+}
+
+impl SongPattern {
+  /// checks if a sequence position is in the pattern, from its start to the end of its loop
+  pub fn contains(&self, sequence: u64) -> bool {
+    (self.start.min(self.loop_start)..=self.loop_end).contains(&sequence)
+  }
+
+  /// checks if the transport jumped from the end of the loop back to its start between two reports
+  pub fn has_looped(&self, previous: &Transport, current: &Transport) -> bool {
+    previous.sequence == self.loop_end
+      && current.sequence == self.loop_start
+      && (current.sequence, current.line) < (previous.sequence, previous.line)
+  }
+}
+
+/// how tin switches to a song
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Transition {
+  /// grid the switch waits for, `Pattern` leaves pattern launches to renoise
+  pub quantization: Quantization,
+  /// sets renoise's bpm to the song's
+  pub sync_bpm: bool,
+  /// milliseconds the bpm glides to the song's from the switch on, 0 jumps to it on the grid
+  #[serde(default)]
+  pub bpm_ramp: u64,
+  /// pattern launched on the switch
+  pub pattern: Option<i64>,
+}
+
+impl Default for Transition {
+  fn default() -> Self {
+    Self {
+      quantization: Quantization::Pattern,
+      sync_bpm: true,
+      bpm_ramp: 0,
+      pattern: None,
+    }
+  }
+}
+
+/// states of the buttons of a song, saved and recalled from the matrix
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Scene {
+  pub name: String,
+  /// buttons without a state are left as they are on recall
+  pub states: HashMap<(i64, i64), SongButtonActionValue>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -25,6 +112,16 @@ pub struct Song {
   pub patterns: HashMap<i64, SongPattern>,
   /// tuple represents x,y
   pub buttons: HashMap<(i64, i64), SongButton>,
+  /// exclusive groups of buttons: activating a member deactivates the others
+  #[serde(default)]
+  pub groups: HashMap<String, HashSet<(i64, i64)>>,
+  /// renoise song loaded when switching to this song, the current one is kept otherwise
+  #[serde(default)]
+  pub file: Option<String>,
+  #[serde(default)]
+  pub transition: Transition,
+  #[serde(default)]
+  pub scenes: Vec<Scene>,
 }
 
 impl Song {
@@ -37,28 +134,74 @@ impl Song {
       bpm: 125.,
       patterns: HashMap::new(),
       buttons: HashMap::new(),
+      groups: HashMap::new(),
+      file: None,
+      transition: Transition::default(),
+      scenes: Vec::new(),
     })
   }
+
+  /// every button sharing an exclusive group with the button at pos
+  pub fn get_group_neighbours(&self, pos: (i64, i64)) -> HashSet<(i64, i64)> {
+    self
+      .groups
+      .values()
+      .filter(|members| members.contains(&pos))
+      .flatten()
+      .filter(|member| **member != pos)
+      .copied()
+      .collect()
+  }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Set {
+  /// see [`migrations`]
+  pub version: u64,
   pub name: String,
   pub authors: String,
   pub stop_seq_pos: u64,
   pub songs: HashMap<String, Song>,
+  pub layout: TrackLayout,
+  /// song ids in the order they are played, songs sorted by order if empty
+  #[serde(default)]
+  pub setlist: Vec<String>,
 }
 
 impl Set {
   pub fn new(name: String, authors: String) -> Result<Set> {
     Ok(Set {
+      version: migrations::CURRENT_VERSION,
       name,
       authors,
       stop_seq_pos: 0,
       songs: HashMap::new(),
+      layout: TrackLayout::default(),
+      setlist: Vec::new(),
     })
   }
 
+  /// reads a set file, upgrading it first if it was written by an older version
+  pub fn load(input: &str) -> Result<Set> {
+    let migrated = migrations::migrate(input)?;
+    Ok(ron::from_str(&migrated)?)
+  }
+
+  /// song ids in the order they are played
+  pub fn get_setlist(&self) -> Vec<String> {
+    if !self.setlist.is_empty() {
+      return self.setlist.clone();
+    }
+
+    let mut songs = self
+      .songs
+      .iter()
+      .map(|(id, s)| (s.order, id.clone()))
+      .collect::<Vec<(i64, String)>>();
+    songs.sort();
+    songs.into_iter().map(|(_, id)| id).collect()
+  }
+
   pub fn get_song_option(&self, song_id: Option<String>) -> Result<Option<&Song>> {
     if let Some(song_id) = song_id {
       let s = self
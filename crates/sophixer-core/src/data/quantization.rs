@@ -0,0 +1,39 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::messages::renoise::Transport;
+
+/// grid an action is launched on, relative to renoise's transport
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Quantization {
+  #[default]
+  Off,
+  Beat,
+  Bar,
+  Pattern,
+}
+
+impl Quantization {
+  /// checks if the transport went over a boundary of this grid between two reports
+  pub fn is_crossed(&self, previous: &Transport, current: &Transport) -> bool {
+    let pattern = current.sequence != previous.sequence || current.line < previous.line;
+    match self {
+      Self::Off => true,
+      Self::Beat => pattern || current.beat() != previous.beat(),
+      Self::Bar => pattern || current.bar() != previous.bar(),
+      Self::Pattern => pattern,
+    }
+  }
+}
+
+impl Display for Quantization {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Off => write!(f, "Off"),
+      Self::Beat => write!(f, "Beat"),
+      Self::Bar => write!(f, "Bar"),
+      Self::Pattern => write!(f, "Pattern"),
+    }
+  }
+}
@@ -0,0 +1,114 @@
+//! devices and parameters of a track, referred to by index or by name
+//!
+//! names are resolved against the device chains Calcium reports when it connects
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::{BTreeMap, HashMap},
+  fmt::Display,
+};
+
+/// a device of a track, or a parameter of a device
+///
+/// indexes are renoise's, starting from 1
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub enum Reference {
+  Index(u64),
+  Name(String),
+}
+
+impl Default for Reference {
+  fn default() -> Self {
+    Self::Index(0)
+  }
+}
+
+impl Display for Reference {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Index(i) => write!(f, "{}", i),
+      Self::Name(name) => write!(f, "\"{}\"", name),
+    }
+  }
+}
+
+/// names as Calcium reports them: the message separators are replaced
+pub fn reported_name(name: &str) -> String {
+  name.replace([',', ';', ':'], "_")
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReportedDevice {
+  pub name: String,
+  pub parameters: BTreeMap<u64, String>,
+}
+
+/// device chains of every track of the renoise project
+#[derive(Debug, Clone, Default)]
+pub struct DeviceChains {
+  pub tracks: HashMap<u64, BTreeMap<u64, ReportedDevice>>,
+}
+
+impl DeviceChains {
+  pub fn insert_device(&mut self, track: u64, device: u64, name: String) {
+    self
+      .tracks
+      .entry(track)
+      .or_default()
+      .entry(device)
+      .or_default()
+      .name = name;
+  }
+
+  pub fn insert_parameter(&mut self, track: u64, device: u64, parameter: u64, name: String) {
+    self
+      .tracks
+      .entry(track)
+      .or_default()
+      .entry(device)
+      .or_default()
+      .parameters
+      .insert(parameter, name);
+  }
+
+  /// index of a device on a track, the first one carrying the name if there are several
+  pub fn resolve_device(&self, track: u64, device: &Reference) -> Result<u64> {
+    match device {
+      Reference::Index(i) => Ok(*i),
+      Reference::Name(name) => {
+        let name = reported_name(name);
+        self
+          .tracks
+          .get(&track)
+          .and_then(|devices| devices.iter().find(|(_, d)| d.name == name))
+          .map(|(i, _)| *i)
+          .ok_or(anyhow::Error::msg(format!(
+            "no device named {} on track {}",
+            device, track
+          )))
+      }
+    }
+  }
+
+  /// index of a parameter of a device, the device being already resolved
+  pub fn resolve_parameter(&self, track: u64, device: u64, parameter: &Reference) -> Result<u64> {
+    match parameter {
+      Reference::Index(i) => Ok(*i),
+      Reference::Name(name) => {
+        let name = reported_name(name);
+        self
+          .tracks
+          .get(&track)
+          .and_then(|devices| devices.get(&device))
+          .and_then(|d| d.parameters.iter().find(|(_, p)| **p == name))
+          .map(|(i, _)| *i)
+          .ok_or(anyhow::Error::msg(format!(
+            "no parameter named {} on device {} of track {}",
+            parameter, device, track
+          )))
+      }
+    }
+  }
+}
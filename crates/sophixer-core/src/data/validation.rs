@@ -0,0 +1,310 @@
+//! checks on sets that the type system can't enforce
+
+use std::{
+  collections::{HashMap, HashSet},
+  fmt::Display,
+};
+
+use crate::data::{
+  Set,
+  buttons::{ActionContext, ActionDescriptor},
+  follow::FollowAction,
+  references::DeviceChains,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+  /// the set works, but probably not as intended
+  Warning,
+  /// the set can't be played as is
+  Error,
+}
+
+/// where a diagnostic comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+  Set,
+  Song(String),
+  Pattern(String, i64),
+  Button(String, (i64, i64)),
+  Group(String, String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+  pub severity: Severity,
+  pub location: Location,
+  pub message: String,
+}
+
+impl Display for Severity {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Warning => write!(f, "warning"),
+      Self::Error => write!(f, "error"),
+    }
+  }
+}
+
+impl Display for Location {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Set => write!(f, "set"),
+      Self::Song(song) => write!(f, "song {}", song),
+      Self::Pattern(song, y) => write!(f, "song {}, pattern {}", song, y),
+      Self::Button(song, (x, y)) => write!(f, "song {}, button {},{}", song, x, y),
+      Self::Group(song, group) => write!(f, "song {}, group {}", song, group),
+    }
+  }
+}
+
+impl Display for Diagnostic {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "{} in {}: {}",
+      self.severity, self.location, self.message
+    )
+  }
+}
+
+impl Set {
+  /// lists everything wrong with the set, sorted by location
+  pub fn validate(&self) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut push = |severity, location, message| {
+      diagnostics.push(Diagnostic {
+        severity,
+        location,
+        message,
+      })
+    };
+
+    let mut group_names = HashSet::new();
+    let mut tracks = HashSet::new();
+    for group in &self.layout.groups {
+      if !group_names.insert(&group.name) {
+        push(
+          Severity::Error,
+          Location::Set,
+          format!("layout has more than one group named {}", group.name),
+        );
+      }
+    }
+    let layout_tracks = self
+      .layout
+      .groups
+      .iter()
+      .flat_map(|g| g.tracks.iter().chain(g.master.iter()))
+      .chain(self.layout.sends.iter().map(|s| &s.track))
+      .chain(std::iter::once(&self.layout.master));
+    for track in layout_tracks {
+      if *track == 0 {
+        push(
+          Severity::Error,
+          Location::Set,
+          String::from("layout uses track 0, renoise tracks start from 1"),
+        );
+      } else if !tracks.insert(*track) {
+        push(
+          Severity::Warning,
+          Location::Set,
+          format!("layout uses track {} more than once", track),
+        );
+      }
+    }
+
+    let ctx = ActionContext {
+      layout: &self.layout,
+      devices: None,
+    };
+
+    let mut orders: HashMap<i64, Vec<&String>> = HashMap::new();
+    for (song_id, song) in &self.songs {
+      orders.entry(song.order).or_default().push(song_id);
+    }
+    for (order, mut song_ids) in orders {
+      if song_ids.len() > 1 {
+        song_ids.sort();
+        push(
+          Severity::Warning,
+          Location::Set,
+          format!(
+            "songs {} share the order {}",
+            song_ids
+              .iter()
+              .map(|s| s.as_str())
+              .collect::<Vec<&str>>()
+              .join(", "),
+            order
+          ),
+        );
+      }
+    }
+
+    for song_id in &self.setlist {
+      if !self.songs.contains_key(song_id) {
+        push(
+          Severity::Error,
+          Location::Set,
+          format!("setlist has song {}, which doesn't exist", song_id),
+        );
+      }
+    }
+
+    for (song_id, song) in &self.songs {
+      if let Some(y) = song.transition.pattern
+        && !song.patterns.contains_key(&y)
+      {
+        push(
+          Severity::Error,
+          Location::Song(song_id.clone()),
+          format!("transition launches pattern {}, which doesn't exist", y),
+        );
+      }
+
+      if song.bpm <= 0. {
+        push(
+          Severity::Error,
+          Location::Song(song_id.clone()),
+          format!("invalid bpm {}", song.bpm),
+        );
+      }
+
+      for (y, pattern) in &song.patterns {
+        let location = Location::Pattern(song_id.clone(), *y);
+        if pattern.loop_start > pattern.loop_end {
+          push(
+            Severity::Error,
+            location.clone(),
+            format!(
+              "loop starts at {} after its end at {}",
+              pattern.loop_start, pattern.loop_end
+            ),
+          );
+        }
+        if *y < 1 {
+          push(
+            Severity::Warning,
+            location.clone(),
+            String::from("outside of the launcher column"),
+          );
+        }
+
+        let targets = match &pattern.follow {
+          FollowAction::Goto(target) => vec![*target],
+          FollowAction::Random(targets) => {
+            if targets.is_empty() {
+              push(
+                Severity::Error,
+                location.clone(),
+                String::from("no patterns to pick from"),
+              );
+            }
+            targets.clone()
+          }
+          _ => Vec::new(),
+        };
+        for target in targets {
+          if !song.patterns.contains_key(&target) {
+            push(
+              Severity::Error,
+              location.clone(),
+              format!("follows pattern {}, which doesn't exist", target),
+            );
+          }
+        }
+        if pattern.follow != FollowAction::None && pattern.loops == 0 {
+          push(
+            Severity::Warning,
+            location,
+            String::from("loops forever, its follow action never happens"),
+          );
+        }
+      }
+
+      for ((x, y), button) in &song.buttons {
+        let location = Location::Button(song_id.clone(), (*x, *y));
+        if *x < 1 || *y < 1 {
+          push(
+            Severity::Warning,
+            location.clone(),
+            String::from("outside of the grid"),
+          );
+        }
+        for (severity, message) in button.action.validate(&ctx) {
+          push(severity, location.clone(), message);
+        }
+      }
+
+      for scene in &song.scenes {
+        let mut states = scene.states.iter().collect::<Vec<_>>();
+        states.sort_by_key(|(pos, _)| **pos);
+        for ((x, y), value) in states {
+          match song.buttons.get(&(*x, *y)) {
+            None => push(
+              Severity::Warning,
+              Location::Song(song_id.clone()),
+              format!(
+                "scene {} has a state for {},{}, which is not a button",
+                scene.name, x, y
+              ),
+            ),
+            Some(button) if button.action.get_color(*value).is_err() => push(
+              Severity::Error,
+              Location::Song(song_id.clone()),
+              format!("scene {} has an invalid state for {},{}", scene.name, x, y),
+            ),
+            Some(_) => {}
+          }
+        }
+      }
+
+      for (group_id, members) in &song.groups {
+        let mut missing = members
+          .iter()
+          .filter(|m| !song.buttons.contains_key(m))
+          .collect::<Vec<&(i64, i64)>>();
+        missing.sort();
+        for (x, y) in missing {
+          push(
+            Severity::Warning,
+            Location::Group(song_id.clone(), group_id.clone()),
+            format!("member {},{} is not a button", x, y),
+          );
+        }
+      }
+    }
+
+    diagnostics.sort_by(|a, b| {
+      format!("{}", a.location)
+        .cmp(&format!("{}", b.location))
+        .then(b.severity.cmp(&a.severity))
+    });
+    diagnostics
+  }
+
+  /// named devices and parameters that the renoise project doesn't have
+  pub fn check_references(&self, devices: &DeviceChains) -> Vec<Diagnostic> {
+    let ctx = ActionContext {
+      layout: &self.layout,
+      devices: Some(devices),
+    };
+
+    let mut diagnostics = Vec::new();
+    for (song_id, song) in &self.songs {
+      for ((x, y), button) in &song.buttons {
+        for message in button.action.unresolved_references(&ctx) {
+          diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            location: Location::Button(song_id.clone(), (*x, *y)),
+            message,
+          });
+        }
+      }
+    }
+
+    diagnostics.sort_by_key(|d| format!("{}", d.location));
+    diagnostics
+  }
+}
@@ -0,0 +1,44 @@
+//! tempo shared between tin instances of a network, see [`intercom::peer`]
+
+use intercom::{InterMessageIncoming, InterMessageOutgoing, InterMessagePrefixed};
+
+/// peers are told apart by an id they pick when joining, as they may share an address
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessageBetweenTins {
+  /// peer, bpm, beat of the peer's timeline when it was sent, and beats per bar
+  Tempo(u64, f64, f64, f64),
+  /// peer leaving the session
+  Leave(u64),
+}
+
+impl InterMessagePrefixed for MessageBetweenTins {
+  fn get_prefix() -> String {
+    String::from("link")
+  }
+}
+
+impl InterMessageIncoming for MessageBetweenTins {
+  fn from_raw(raw: Vec<&str>) -> Option<Self> {
+    match raw[..] {
+      ["tempo", peer, bpm, beat, quantum] => Some(Self::Tempo(
+        peer.parse().ok()?,
+        bpm.parse().ok()?,
+        beat.parse().ok()?,
+        quantum.parse().ok()?,
+      )),
+      ["leave", peer] => Some(Self::Leave(peer.parse().ok()?)),
+      _ => None,
+    }
+  }
+}
+
+impl InterMessageOutgoing for MessageBetweenTins {
+  fn to_raw(self) -> Result<String, intercom::InterError> {
+    match self {
+      Self::Tempo(peer, bpm, beat, quantum) => {
+        Ok(format!("tempo,{},{},{},{}", peer, bpm, beat, quantum))
+      }
+      Self::Leave(peer) => Ok(format!("leave,{}", peer)),
+    }
+  }
+}
@@ -1,9 +1,50 @@
 use intercom::{InterMessageIncoming, InterMessageOutgoing, InterMessagePrefixed};
 
+/// playback position reported by renoise
+///
+/// sequence and line are converted to start at 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transport {
+  pub playing: bool,
+  pub sequence: u64,
+  pub line: u64,
+  pub beats: f64,
+  pub beats_per_bar: u64,
+}
+
+impl Transport {
+  pub fn beat(&self) -> i64 {
+    self.beats.floor() as i64
+  }
+
+  pub fn bar(&self) -> i64 {
+    (self.beats / self.beats_per_bar.max(1) as f64).floor() as i64
+  }
+
+  fn from_raw(raw: &[&str]) -> Option<Self> {
+    Some(Self {
+      playing: raw[0] == "1",
+      sequence: raw[1].parse::<u64>().ok()?.checked_sub(1)?,
+      line: raw[2].parse::<u64>().ok()?.checked_sub(1)?,
+      beats: raw[3].parse().ok()?,
+      beats_per_bar: raw[4].parse().ok()?,
+    })
+  }
+}
+
 #[derive(Debug, Clone)]
 pub enum MessageFromRenoise {
   Hello,
   Goodbye,
+  Transport(Transport),
+  /// bpm, reported when it changes
+  Bpm(f64),
+  /// track, device and name, reported on connect
+  Device(u64, u64, String),
+  /// track, device, parameter and name, reported on connect
+  Parameter(u64, u64, u64, String),
+  /// every device and parameter got reported
+  DevicesReported,
 }
 
 impl InterMessagePrefixed for MessageFromRenoise {
@@ -18,6 +59,32 @@ impl InterMessageIncoming for MessageFromRenoise {
       1 => match raw[0] {
         "hello" => Some(Self::Hello),
         "goodbye" => Some(Self::Goodbye),
+        "devicesReported" => Some(Self::DevicesReported),
+        _ => None,
+      },
+      2 => match raw[0] {
+        "bpm" => Some(Self::Bpm(raw[1].parse().ok()?)),
+        _ => None,
+      },
+      4 => match raw[0] {
+        "device" => Some(Self::Device(
+          raw[1].parse().ok()?,
+          raw[2].parse().ok()?,
+          raw[3].to_string(),
+        )),
+        _ => None,
+      },
+      5 => match raw[0] {
+        "parameter" => Some(Self::Parameter(
+          raw[1].parse().ok()?,
+          raw[2].parse().ok()?,
+          raw[3].parse().ok()?,
+          raw[4].to_string(),
+        )),
+        _ => None,
+      },
+      6 => match raw[0] {
+        "transport" => Transport::from_raw(&raw[1..]).map(Self::Transport),
         _ => None,
       },
       _ => None,
@@ -32,6 +99,8 @@ pub enum MessageToRenoise {
   /// set bool to true for forcing replay
   PlaySection(u64, bool),
   SetLoop(u64, u64),
+  /// plays on from where renoise is
+  StartTransport,
   StopTransport,
   MuteTrack(u64, bool),
   MuteTrackSequenceSlot(u64, u64, bool),
@@ -39,6 +108,8 @@ pub enum MessageToRenoise {
   SetParameterValue(u64, u64, u64, f64),
   SetBPM(f64),
   SetMasterVolume(f64),
+  /// moves the playback to a number of beats since the start of the song
+  SetPlaybackBeats(f64),
 }
 
 impl InterMessageOutgoing for MessageToRenoise {
@@ -48,6 +119,7 @@ impl InterMessageOutgoing for MessageToRenoise {
       Self::LoadSong(s) => Ok(format!("loadSong,{}", s)),
       Self::PlaySection(s, f) => Ok(format!("playSection,{},{}", s + 1, if f { 1 } else { 0 })),
       Self::SetLoop(s, e) => Ok(format!("setLoop,{},{}", s + 1, e + 1)),
+      Self::StartTransport => Ok(String::from("startTransport")),
       Self::StopTransport => Ok(String::from("stopTransport")),
       Self::MuteTrack(t, b) => Ok(format!("muteTrack,{},{}", t, if b { 1 } else { 0 })),
       Self::MuteTrackSequenceSlot(t, s, b) => Ok(format!(
@@ -67,6 +139,7 @@ impl InterMessageOutgoing for MessageToRenoise {
       }
       Self::SetBPM(bpm) => Ok(format!("setBPM,{}", bpm)),
       Self::SetMasterVolume(vol) => Ok(format!("setMasterVolume,{}", vol)),
+      Self::SetPlaybackBeats(beats) => Ok(format!("setPlaybackBeats,{}", beats)),
     }
   }
 }
@@ -0,0 +1,51 @@
+use sophixer_core::{data::SongPattern, messages::renoise::Transport};
+
+fn at(sequence: u64, line: u64) -> Transport {
+  Transport {
+    playing: true,
+    sequence,
+    line,
+    beats: 0.,
+    beats_per_bar: 4,
+  }
+}
+
+#[test]
+fn multi_pattern_loop() {
+  let pattern = SongPattern {
+    start: 0,
+    loop_start: 1,
+    loop_end: 3,
+    ..Default::default()
+  };
+  assert!(pattern.has_looped(&at(3, 63), &at(1, 0)));
+  assert!(!pattern.has_looped(&at(2, 63), &at(3, 0)));
+  assert!(!pattern.has_looped(&at(0, 63), &at(1, 0)));
+}
+
+#[test]
+fn single_pattern_loop() {
+  let pattern = SongPattern {
+    start: 2,
+    loop_start: 2,
+    loop_end: 2,
+    ..Default::default()
+  };
+  assert!(pattern.has_looped(&at(2, 63), &at(2, 0)));
+  assert!(!pattern.has_looped(&at(2, 10), &at(2, 11)));
+  assert!(!pattern.has_looped(&at(1, 63), &at(2, 0)));
+}
+
+#[test]
+fn pattern_with_intro() {
+  let pattern = SongPattern {
+    start: 4,
+    loop_start: 5,
+    loop_end: 6,
+    ..Default::default()
+  };
+  assert!(pattern.contains(4));
+  assert!(pattern.contains(6));
+  assert!(!pattern.contains(3));
+  assert!(!pattern.contains(7));
+}
@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+
+use sophixer_core::data::{
+  Song,
+  buttons::{
+    ActionDescriptor, SongButtonAction, SongButtonActionValue,
+    cycle_effect_parameter_value::{CycleEffectParameterValue, ParameterValue},
+    play_section::PlaySection,
+    toggle_channels::ToggleChannels,
+  },
+};
+
+#[test]
+fn neighbours_share_a_group() {
+  let mut song = Song::new(String::from("song"), String::from("yyna")).unwrap();
+  song.groups.insert(
+    String::from("leads"),
+    HashSet::from([(1, 1), (2, 1), (3, 1)]),
+  );
+  song
+    .groups
+    .insert(String::from("fx"), HashSet::from([(1, 1), (1, 2)]));
+
+  assert_eq!(
+    song.get_group_neighbours((1, 1)),
+    HashSet::from([(2, 1), (3, 1), (1, 2)])
+  );
+  assert_eq!(song.get_group_neighbours((1, 2)), HashSet::from([(1, 1)]));
+  assert!(song.get_group_neighbours((4, 4)).is_empty());
+}
+
+#[test]
+fn toggles_are_active_when_true() {
+  let action = SongButtonAction::ToggleChannels(ToggleChannels {
+    default: true,
+    ..Default::default()
+  });
+
+  assert!(action.is_active(SongButtonActionValue::Boolean(true)));
+  assert!(!action.is_active(SongButtonActionValue::Boolean(false)));
+  assert_eq!(action.get_inactive(), SongButtonActionValue::Boolean(false));
+  assert!(!action.is_active(action.get_inactive()));
+}
+
+#[test]
+fn cycles_are_active_past_their_first_value() {
+  let action = SongButtonAction::CycleEffectParameterValue(CycleEffectParameterValue {
+    default: 1,
+    cycles: vec![ParameterValue::default(); 3],
+    ..Default::default()
+  });
+
+  assert!(!action.is_active(SongButtonActionValue::Number(0)));
+  assert!(action.is_active(SongButtonActionValue::Number(1)));
+  assert!(action.is_active(SongButtonActionValue::Number(2)));
+  // the default doesn't matter, switching off goes back to the first value
+  assert_eq!(action.get_inactive(), SongButtonActionValue::Number(0));
+  assert!(!action.is_active(action.get_inactive()));
+}
+
+#[test]
+fn stateless_actions_are_never_active() {
+  let action = SongButtonAction::PlaySection(PlaySection::default());
+
+  assert!(!action.is_active(SongButtonActionValue::None));
+  assert_eq!(action.get_inactive(), SongButtonActionValue::None);
+}
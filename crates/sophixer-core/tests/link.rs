@@ -0,0 +1,23 @@
+use intercom::{InterMessageIncoming, InterMessageOutgoing};
+use sophixer_core::messages::link::MessageBetweenTins;
+
+fn round_trip(message: MessageBetweenTins) -> Option<MessageBetweenTins> {
+  let raw = message.to_raw().unwrap();
+  MessageBetweenTins::from_raw(raw.split(",").collect())
+}
+
+#[test]
+fn messages_round_trip() {
+  for message in [
+    MessageBetweenTins::Tempo(12345678901234567890, 126.5, 1024.25, 4.),
+    MessageBetweenTins::Leave(42),
+  ] {
+    assert_eq!(round_trip(message.clone()), Some(message));
+  }
+}
+
+#[test]
+fn unknown_messages_are_refused() {
+  assert_eq!(MessageBetweenTins::from_raw(vec!["tempo", "1", "120"]), None);
+  assert_eq!(MessageBetweenTins::from_raw(vec!["leave", "me"]), None);
+}
@@ -0,0 +1,152 @@
+use sophixer_core::{
+  data::{
+    buttons::{
+      ActionContext, ActionDescriptor, SongButtonAction, SongButtonActionValue,
+      cycle_effect_parameter_value::{CycleEffectParameterValue, ParameterValue},
+      macros::{Macro, MacroStep},
+      play_sample::PlaySample,
+      play_section::PlaySection,
+      script::Script,
+    },
+    layout::TrackLayout,
+    validation::Severity,
+  },
+  messages::renoise::MessageToRenoise,
+};
+
+fn section_and_cycle() -> SongButtonAction {
+  SongButtonAction::Macro(Macro {
+    steps: vec![
+      MacroStep {
+        action: SongButtonAction::PlaySection(PlaySection {
+          start: 4,
+          loop_start: 4,
+          loop_end: 7,
+          ..Default::default()
+        }),
+        delay: 0,
+      },
+      MacroStep {
+        action: SongButtonAction::CycleEffectParameterValue(CycleEffectParameterValue {
+          cycles: vec![
+            ParameterValue {
+              value: 0.,
+              color: [0, 0, 0],
+            },
+            ParameterValue {
+              value: 1.,
+              color: [0, 0, 0],
+            },
+          ],
+          ..Default::default()
+        }),
+        delay: 0,
+      },
+    ],
+    ..Default::default()
+  })
+}
+
+#[test]
+fn stateless_steps_fire_when_switched_on() {
+  let layout = TrackLayout::default();
+  let ctx = ActionContext {
+    layout: &layout,
+    devices: None,
+  };
+  let action = section_and_cycle();
+
+  let on = action
+    .create_renoise_message(SongButtonActionValue::Boolean(true), &ctx)
+    .unwrap();
+  match on.as_slice() {
+    [
+      MessageToRenoise::PlaySection(4, false),
+      MessageToRenoise::SetLoop(4, 7),
+      MessageToRenoise::SetParameterValue(_, _, _, v),
+    ] => assert_eq!(*v, 1.),
+    _ => panic!("incorrect messages"),
+  }
+
+  let off = action
+    .create_renoise_message(SongButtonActionValue::Boolean(false), &ctx)
+    .unwrap();
+  match off.as_slice() {
+    [MessageToRenoise::SetParameterValue(_, _, _, v)] => assert_eq!(*v, 0.),
+    _ => panic!("incorrect messages"),
+  }
+}
+
+#[test]
+fn stateless_steps_are_not_restored() {
+  let layout = TrackLayout::default();
+  let ctx = ActionContext {
+    layout: &layout,
+    devices: None,
+  };
+  let action = section_and_cycle();
+
+  let restored = action
+    .create_restore_renoise_message(SongButtonActionValue::Boolean(true), &ctx)
+    .unwrap();
+  match restored.as_slice() {
+    [(_, MessageToRenoise::SetParameterValue(_, _, _, v))] => assert_eq!(*v, 1.),
+    _ => panic!("incorrect messages"),
+  }
+
+  let section = SongButtonAction::PlaySection(PlaySection::default());
+  assert!(
+    section
+      .create_restore_renoise_message(SongButtonActionValue::None, &ctx)
+      .unwrap()
+      .is_empty()
+  );
+}
+
+#[test]
+fn sample_steps_are_refused() {
+  let layout = TrackLayout::default();
+  let ctx = ActionContext {
+    layout: &layout,
+    devices: None,
+  };
+  let action = SongButtonAction::Macro(Macro {
+    steps: vec![MacroStep {
+      action: SongButtonAction::PlaySample(PlaySample::default()),
+      delay: 0,
+    }],
+    ..Default::default()
+  });
+
+  assert!(
+    action
+      .validate(&ctx)
+      .iter()
+      .any(|(severity, message)| *severity == Severity::Error
+        && message == "step 0: PlaySample isn't implemented yet")
+  );
+}
+
+#[test]
+fn script_steps_are_refused() {
+  let layout = TrackLayout::default();
+  let ctx = ActionContext {
+    layout: &layout,
+    devices: None,
+  };
+  let action = SongButtonAction::Macro(Macro {
+    steps: vec![MacroStep {
+      action: SongButtonAction::Script(Script::default()),
+      delay: 0,
+    }],
+    ..Default::default()
+  });
+
+  assert!(
+    action
+      .validate(&ctx)
+      .iter()
+      .any(|(severity, message)| *severity == Severity::Error
+        && message == "step 0: scripts can't be macro steps")
+  );
+}
@@ -0,0 +1,110 @@
+use sophixer_core::data::{
+  Set,
+  buttons::SongButtonAction,
+  channels::Channel,
+  layout::TrackLayout,
+  migrations::{CURRENT_VERSION, migrate, read_version, step},
+};
+
+/// a set as written before versioning
+const SET_V0: &str = r#"(
+  name: "test set",
+  authors: "yyna",
+  stop_seq_pos: 12,
+  songs: {
+    "intro": (
+      name: "Intro",
+      authors: "yyna",
+      order: 1,
+      color: (255, 0, 0),
+      bpm: 128.0,
+      patterns: {
+        1: (start: 0, loop_start: 0, loop_end: 1, color: (0, 255, 0)),
+      },
+      buttons: {
+        (1, 1): (
+          action: ToggleChannels((
+            channels: [Lead(1), Drum(2)],
+            default: true,
+            color_off: (0, 0, 0),
+            color_on: (255, 255, 255),
+          )),
+        ),
+      },
+    ),
+  },
+)"#;
+
+#[test]
+fn unversioned_set_is_version_0() {
+  assert_eq!(read_version(SET_V0).unwrap(), 0);
+}
+
+#[test]
+fn v0_to_current() {
+  let migrated = migrate(SET_V0).unwrap();
+  assert_eq!(read_version(&migrated).unwrap(), CURRENT_VERSION);
+
+  let set = Set::load(SET_V0).unwrap();
+  assert_eq!(set.version, CURRENT_VERSION);
+  assert_eq!(set.name, "test set");
+  assert_eq!(set.stop_seq_pos, 12);
+
+  let song = set.songs.get("intro").unwrap();
+  assert_eq!(song.order, 1);
+  assert_eq!(song.bpm, 128.0);
+  assert_eq!(song.patterns.get(&1).unwrap().loop_end, 1);
+
+  let button = song.buttons.get(&(1, 1)).unwrap();
+  match &button.action {
+    SongButtonAction::ToggleChannels(inner) => {
+      assert!(inner.default);
+      assert!(
+        inner
+          .channels
+          .contains(&Channel::Track(String::from("lead"), 1))
+      );
+      assert!(
+        inner
+          .channels
+          .contains(&Channel::Track(String::from("drum"), 2))
+      );
+    }
+    _ => panic!("incorrect action"),
+  }
+}
+
+#[test]
+fn v1_gets_default_layout() {
+  let v1 = step(0, SET_V0).unwrap();
+  assert_eq!(read_version(&v1).unwrap(), 1);
+
+  let v2 = step(1, &v1).unwrap();
+  assert_eq!(read_version(&v2).unwrap(), 2);
+  // fields added after version 2 aren't written by its migration
+  assert!(!v2.contains("setlist"));
+
+  let set: Set = ron::from_str(&v2).unwrap();
+  assert_eq!(set.layout, TrackLayout::default());
+  assert_eq!(
+    set
+      .layout
+      .resolve(&Channel::Track(String::from("drum"), 2))
+      .unwrap(),
+    9
+  );
+}
+
+#[test]
+fn current_version_is_untouched() {
+  let set = Set::new(String::from("current"), String::from("yyna")).unwrap();
+  let written = ron::to_string(&set).unwrap();
+  assert_eq!(migrate(&written).unwrap(), written);
+}
+
+#[test]
+fn newer_version_is_refused() {
+  let written = format!("(version: {}, name: \"\")", CURRENT_VERSION + 1);
+  assert!(migrate(&written).is_err());
+  assert!(Set::load(&written).is_err());
+}
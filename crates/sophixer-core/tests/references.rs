@@ -0,0 +1,97 @@
+use intercom::InterMessageIncoming;
+use sophixer_core::{
+  data::{
+    Set, Song,
+    buttons::{
+      SongButton, SongButtonAction,
+      cycle_effect_parameter_value::{CycleEffectParameterValue, ParameterValue},
+      toggle_effect_bypass::ToggleEffectBypass,
+    },
+    channels::Channel,
+    references::{DeviceChains, Reference},
+    validation::Location,
+  },
+  messages::renoise::MessageFromRenoise,
+};
+
+fn chains() -> DeviceChains {
+  let mut chains = DeviceChains::default();
+  for raw in [
+    "device,1,1,TrackVolPan",
+    "device,1,2,Reverb",
+    "parameter,1,2,1,Room Size",
+    "parameter,1,2,2,Dry_Wet",
+  ] {
+    match MessageFromRenoise::from_raw(raw.split(",").collect()).unwrap() {
+      MessageFromRenoise::Device(t, d, name) => chains.insert_device(t, d, name),
+      MessageFromRenoise::Parameter(t, d, p, name) => chains.insert_parameter(t, d, p, name),
+      _ => panic!("incorrect message"),
+    }
+  }
+  chains
+}
+
+#[test]
+fn indexes_and_names_are_read() {
+  let old: ToggleEffectBypass = ron::from_str(
+    "(track: Master, effect: 2, default: true, color_off: (0, 0, 0), color_on: (0, 0, 0))",
+  )
+  .unwrap();
+  assert_eq!(old.effect, Reference::Index(2));
+
+  let named: ToggleEffectBypass = ron::from_str(
+    "(track: Master, effect: \"Reverb\", default: true, color_off: (0, 0, 0), color_on: (0, 0, 0))",
+  )
+  .unwrap();
+  assert_eq!(named.effect, Reference::Name(String::from("Reverb")));
+}
+
+#[test]
+fn names_resolve() {
+  let chains = chains();
+  let reverb = Reference::Name(String::from("Reverb"));
+  assert_eq!(chains.resolve_device(1, &reverb).unwrap(), 2);
+  assert_eq!(chains.resolve_device(1, &Reference::Index(5)).unwrap(), 5);
+  assert!(chains.resolve_device(2, &reverb).is_err());
+
+  // separators are replaced in reported names
+  let wet = Reference::Name(String::from("Dry,Wet"));
+  assert_eq!(chains.resolve_parameter(1, 2, &wet).unwrap(), 2);
+  assert!(chains.resolve_parameter(1, 1, &wet).is_err());
+}
+
+#[test]
+fn unresolved_references_are_flagged() {
+  let mut set = Set::new(String::new(), String::new()).unwrap();
+  set.layout.master = 1;
+  let mut song = Song::new(String::from("song"), String::new()).unwrap();
+  song.buttons.insert(
+    (1, 1),
+    SongButton::new(SongButtonAction::CycleEffectParameterValue(
+      CycleEffectParameterValue {
+        track: Channel::Master,
+        effect: Reference::Name(String::from("Reverb")),
+        param: Reference::Name(String::from("Room Size")),
+        default: 0,
+        cycles: vec![ParameterValue::default()],
+      },
+    ))
+    .unwrap(),
+  );
+  song.buttons.insert(
+    (2, 1),
+    SongButton::new(SongButtonAction::ToggleEffectBypass(ToggleEffectBypass {
+      effect: Reference::Name(String::from("Delay")),
+      ..Default::default()
+    }))
+    .unwrap(),
+  );
+  set.songs.insert(String::from("song"), song);
+
+  let diagnostics = set.check_references(&chains());
+  assert_eq!(diagnostics.len(), 1);
+  assert_eq!(
+    diagnostics[0].location,
+    Location::Button(String::from("song"), (2, 1))
+  );
+}
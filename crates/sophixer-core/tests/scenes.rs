@@ -0,0 +1,99 @@
+use sophixer_core::{
+  data::{
+    Scene, Set, Song,
+    buttons::{
+      ActionContext, ActionDescriptor, SongButton, SongButtonAction, SongButtonActionValue,
+      cycle_effect_parameter_value::{CycleEffectParameterValue, ParameterValue},
+      toggle_channels::ToggleChannels,
+    },
+    layout::TrackLayout,
+    validation::{Location, Severity},
+  },
+  messages::renoise::MessageToRenoise,
+};
+
+fn cycle() -> SongButtonAction {
+  SongButtonAction::CycleEffectParameterValue(CycleEffectParameterValue {
+    cycles: vec![
+      ParameterValue {
+        value: 0.,
+        color: [0, 0, 0],
+      },
+      ParameterValue {
+        value: 1.,
+        color: [0, 0, 0],
+      },
+    ],
+    ..Default::default()
+  })
+}
+
+#[test]
+fn cycles_morph() {
+  let layout = TrackLayout::default();
+  let ctx = ActionContext {
+    layout: &layout,
+    devices: None,
+  };
+  let action = cycle();
+
+  let messages = action
+    .morph_renoise_message(
+      SongButtonActionValue::Number(0),
+      SongButtonActionValue::Number(1),
+      0.25,
+      &ctx,
+    )
+    .unwrap();
+  match messages.as_slice() {
+    [MessageToRenoise::SetParameterValue(_, _, _, v)] => assert_eq!(*v, 0.25),
+    _ => panic!("incorrect messages"),
+  }
+
+  let toggle = SongButtonAction::ToggleChannels(ToggleChannels::default());
+  assert!(
+    toggle
+      .morph_renoise_message(
+        SongButtonActionValue::Boolean(false),
+        SongButtonActionValue::Boolean(true),
+        0.5,
+        &ctx,
+      )
+      .unwrap()
+      .is_empty()
+  );
+}
+
+#[test]
+fn scene_states_are_checked() {
+  let mut set = Set::new(String::new(), String::new()).unwrap();
+  let mut song = Song::new(String::from("song"), String::new()).unwrap();
+  song
+    .buttons
+    .insert((1, 1), SongButton::new(cycle()).unwrap());
+  song.scenes.push(Scene {
+    name: String::from("drop"),
+    states: [
+      ((1, 1), SongButtonActionValue::Boolean(true)),
+      ((2, 1), SongButtonActionValue::Number(0)),
+    ]
+    .into_iter()
+    .collect(),
+  });
+  set.songs.insert(String::from("song"), song);
+
+  let diagnostics = set.validate();
+  let scene_diagnostics = diagnostics
+    .iter()
+    .filter(|d| d.location == Location::Song(String::from("song")))
+    .map(|d| d.severity)
+    .collect::<Vec<Severity>>();
+  assert_eq!(scene_diagnostics, vec![Severity::Error, Severity::Warning]);
+
+  let written = ron::to_string(&set).unwrap();
+  let read = Set::load(&written).unwrap();
+  assert_eq!(
+    read.songs.get("song").unwrap().scenes,
+    set.songs.get("song").unwrap().scenes
+  );
+}
@@ -0,0 +1,96 @@
+use sophixer_core::data::{
+  Set, Song, SongPattern,
+  buttons::{
+    SongButton, SongButtonAction,
+    cycle_effect_parameter_value::{CycleEffectParameterValue, ParameterValue},
+  },
+  validation::{Location, Severity},
+};
+
+fn song_with_cycle(default: usize, cycles: usize) -> Song {
+  let mut song = Song::new(String::from("song"), String::new()).unwrap();
+  song.buttons.insert(
+    (1, 1),
+    SongButton::new(SongButtonAction::CycleEffectParameterValue(
+      CycleEffectParameterValue {
+        default,
+        cycles: vec![ParameterValue::default(); cycles],
+        ..Default::default()
+      },
+    ))
+    .unwrap(),
+  );
+  song
+}
+
+#[test]
+fn valid_set() {
+  let mut set = Set::new(String::new(), String::new()).unwrap();
+  set.songs.insert(String::from("a"), song_with_cycle(1, 2));
+  assert!(set.validate().is_empty());
+}
+
+#[test]
+fn invalid_set() {
+  let mut set = Set::new(String::new(), String::new()).unwrap();
+
+  let mut a = song_with_cycle(2, 2);
+  a.patterns.insert(
+    1,
+    SongPattern {
+      loop_start: 3,
+      loop_end: 2,
+      ..Default::default()
+    },
+  );
+  a.groups
+    .insert(String::from("bass"), [(1, 1), (2, 1)].into_iter().collect());
+  set.songs.insert(String::from("a"), a);
+  set.songs.insert(String::from("b"), song_with_cycle(0, 0));
+
+  let diagnostics = set.validate();
+  let find = |location: Location| {
+    diagnostics
+      .iter()
+      .find(|d| d.location == location)
+      .unwrap_or_else(|| panic!("no diagnostic for {}", location))
+  };
+
+  assert_eq!(find(Location::Set).severity, Severity::Warning);
+  assert_eq!(
+    find(Location::Button(String::from("a"), (1, 1))).severity,
+    Severity::Error
+  );
+  assert_eq!(
+    find(Location::Button(String::from("b"), (1, 1))).severity,
+    Severity::Error
+  );
+  assert_eq!(
+    find(Location::Pattern(String::from("a"), 1)).severity,
+    Severity::Error
+  );
+  assert_eq!(
+    find(Location::Group(String::from("a"), String::from("bass"))).severity,
+    Severity::Warning
+  );
+}
+
+#[test]
+fn setlist_falls_back_to_order() {
+  let mut set = Set::new(String::new(), String::new()).unwrap();
+  for (id, order) in [("b", 2), ("a", 1), ("c", 3)] {
+    let mut song = Song::new(String::from(id), String::new()).unwrap();
+    song.order = order;
+    set.songs.insert(String::from(id), song);
+  }
+  assert_eq!(set.get_setlist(), vec!["a", "b", "c"]);
+
+  set.setlist = vec![String::from("c"), String::from("a"), String::from("d")];
+  assert_eq!(set.get_setlist(), vec!["c", "a", "d"]);
+  assert!(
+    set
+      .validate()
+      .iter()
+      .any(|d| d.location == Location::Set && d.severity == Severity::Error)
+  );
+}
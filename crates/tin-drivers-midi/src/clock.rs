@@ -0,0 +1,229 @@
+//! midi clock, sent to or followed from any midi port
+//!
+//! the clock ticks 24 times per quarter note, song positions count sixteenth notes
+
+use crate::devices::{get_in_port, get_out_port};
+use crate::{MidiDriverError, MidiInputMessage, MidiOutputMessage};
+use midir::MidiInputConnection;
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+pub const TICKS_PER_BEAT: u32 = 24;
+
+/// ticks averaged into the followed bpm
+const FOLLOWED_TICKS: usize = TICKS_PER_BEAT as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockMessage {
+  Tick,
+  Start,
+  Continue,
+  Stop,
+  /// sixteenth notes since the start of the song
+  SongPosition(u16),
+}
+
+impl MidiInputMessage for ClockMessage {
+  fn from_raw(raw: &[u8]) -> Option<Self> {
+    match raw {
+      [248] => Some(Self::Tick),
+      [250] => Some(Self::Start),
+      [251] => Some(Self::Continue),
+      [252] => Some(Self::Stop),
+      [242, lsb, msb] => Some(Self::SongPosition(
+        (*lsb as u16 & 127) | ((*msb as u16 & 127) << 7),
+      )),
+      _ => None,
+    }
+  }
+}
+
+impl MidiOutputMessage for ClockMessage {
+  fn to_raw(self) -> Result<Vec<u8>, MidiDriverError> {
+    match self {
+      Self::Tick => Ok(vec![248]),
+      Self::Start => Ok(vec![250]),
+      Self::Continue => Ok(vec![251]),
+      Self::Stop => Ok(vec![252]),
+      Self::SongPosition(p) => {
+        if p > 16383 {
+          return Err(MidiDriverError::InvalidPosition(
+            "midi clock".to_string(),
+            format!("song position {}", p),
+          ));
+        }
+        Ok(vec![242, (p & 127) as u8, (p >> 7) as u8])
+      }
+    }
+  }
+}
+
+enum SenderCommand {
+  Bpm(f64),
+  Send(ClockMessage),
+  Close,
+}
+
+/// sends ticks at a bpm from its own thread, so they don't wait for the caller's loop
+pub struct MidiClockSender {
+  tx: mpsc::Sender<SenderCommand>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl MidiClockSender {
+  /// connects to the first output port whose name contains `port`
+  pub fn connect(port: &str, bpm: f64) -> Result<Self, MidiDriverError> {
+    debug!("clock -- starting sender on {}...", port);
+
+    let (midi_out, out_port) = get_out_port(port)?;
+    let (tx, rx) = mpsc::channel::<SenderCommand>();
+    let (ready_tx, ready_rx) = mpsc::channel::<Result<(), MidiDriverError>>();
+
+    let thread = thread::spawn(move || {
+      let mut conn_out = match midi_out.connect(&out_port, "clock output writer") {
+        Ok(conn_out) => {
+          let _ = ready_tx.send(Ok(()));
+          conn_out
+        }
+        Err(e) => {
+          let _ = ready_tx.send(Err(MidiDriverError::MidirConnectOutputError(e.to_string())));
+          return;
+        }
+      };
+
+      let mut bpm = bpm;
+      let mut next_tick = Instant::now();
+      loop {
+        let now = Instant::now();
+        if now >= next_tick {
+          if let Err(e) = conn_out.send(&[248]) {
+            warn!("clock -- couldn't send tick: {}", e);
+          }
+          next_tick += Duration::from_secs_f64(60. / (bpm * TICKS_PER_BEAT as f64));
+          // a stalled thread skips the ticks it missed instead of bursting them
+          if next_tick < now {
+            next_tick = now;
+          }
+          continue;
+        }
+
+        match rx.recv_timeout(next_tick - now) {
+          Ok(SenderCommand::Bpm(b)) => bpm = b,
+          Ok(SenderCommand::Send(message)) => match message.to_raw() {
+            Ok(raw) => {
+              if let Err(e) = conn_out.send(&raw) {
+                warn!("clock -- couldn't send {:?}: {}", message, e);
+              }
+            }
+            Err(e) => warn!("clock -- {}", e),
+          },
+          Ok(SenderCommand::Close) | Err(mpsc::RecvTimeoutError::Disconnected) => break,
+          Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
+      }
+    });
+
+    ready_rx
+      .recv()
+      .map_err(|e| MidiDriverError::MidirConnectOutputError(e.to_string()))??;
+
+    Ok(Self {
+      tx,
+      thread: Some(thread),
+    })
+  }
+
+  pub fn set_bpm(&mut self, bpm: f64) {
+    if bpm > 0. {
+      let _ = self.tx.send(SenderCommand::Bpm(bpm));
+    }
+  }
+
+  /// sends start, continue, stop or song position messages, ticks are sent by the clock itself
+  pub fn send(&mut self, message: ClockMessage) {
+    let _ = self.tx.send(SenderCommand::Send(message));
+  }
+
+  pub fn close(&mut self) {
+    debug!("clock -- closing sender...");
+    let _ = self.tx.send(SenderCommand::Close);
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+/// reads the clock of another device, and the bpm it ticks at
+pub struct MidiClockReceiver {
+  _conn_in: MidiInputConnection<()>,
+  rx: mpsc::Receiver<(u64, ClockMessage)>,
+
+  /// timestamps of the last ticks, in microseconds
+  ticks: VecDeque<u64>,
+}
+
+impl MidiClockReceiver {
+  /// connects to the first input port whose name contains `port`
+  pub fn connect(port: &str) -> Result<Self, MidiDriverError> {
+    debug!("clock -- starting receiver on {}...", port);
+
+    let (midi_in, in_port) = get_in_port(port)?;
+    let (tx, rx) = mpsc::channel::<(u64, ClockMessage)>();
+
+    let _conn_in = midi_in
+      .connect(
+        &in_port,
+        "clock input reader",
+        move |stamp, raw_message, _| {
+          if let Some(message) = ClockMessage::from_raw(raw_message) {
+            let _ = tx.send((stamp, message));
+          }
+        },
+        (),
+      )
+      .map_err(|e| MidiDriverError::MidirConnectInputError(e.to_string()))?;
+
+    Ok(Self {
+      _conn_in,
+      rx,
+      ticks: VecDeque::new(),
+    })
+  }
+
+  /// reads every message but ticks, which only go into the bpm
+  pub fn read(&mut self) -> VecDeque<ClockMessage> {
+    let mut q = VecDeque::new();
+    while let Ok((stamp, message)) = self.rx.try_recv() {
+      match message {
+        ClockMessage::Tick => {
+          self.ticks.push_back(stamp);
+          if self.ticks.len() > FOLLOWED_TICKS + 1 {
+            self.ticks.pop_front();
+          }
+        }
+        // a restarted clock may not tick at the same pace
+        ClockMessage::Start => {
+          self.ticks.clear();
+          q.push_back(message);
+        }
+        _ => q.push_back(message),
+      }
+    }
+    q
+  }
+
+  /// bpm the clock ticks at, once it ticked for a beat
+  pub fn bpm(&self) -> Option<f64> {
+    if self.ticks.len() <= FOLLOWED_TICKS {
+      return None;
+    }
+    let (first, last) = (self.ticks.front()?, self.ticks.back()?);
+    let tick = (last.checked_sub(*first)? as f64 / 1_000_000.) / (self.ticks.len() - 1) as f64;
+    if tick <= 0. {
+      return None;
+    }
+    Some(60. / (tick * TICKS_PER_BEAT as f64))
+  }
+}
@@ -2,18 +2,17 @@ use crate::devices::launch_control_xl_mk2::LCXL2Position;
 use crate::devices::launch_control_xl_mk2::input::LCXL2InputMessage;
 use crate::devices::launch_control_xl_mk2::output::LCXL2OutputMessage;
 use crate::devices::launch_control_xl_mk2::visual::LCXL2Visual;
-use crate::devices::{get_in_port, get_out_port};
+use crate::devices::{Connection, InputHook, get_in_port, get_out_port};
 use crate::{
   MidiDriver, MidiDriverError, MidiInputMessage, MidiOutputMessage, MidiPhysicalPosition,
   MidiPhysicalState, MidiVisual,
 };
-use midir::{MidiInputConnection, MidiOutputConnection};
 use std::collections::{HashMap, VecDeque};
 use std::sync::mpsc;
 
 pub struct LCXL2Driver {
-  _conn_in: MidiInputConnection<()>,
-  conn_out: MidiOutputConnection,
+  connection: Connection,
+  on_input: InputHook,
   rx: mpsc::Receiver<LCXL2InputMessage>,
 
   effective_visual: HashMap<u8, LCXL2Visual>,
@@ -22,32 +21,36 @@ pub struct LCXL2Driver {
   physical_states: HashMap<u8, MidiPhysicalState>,
 }
 
-impl MidiDriver<LCXL2InputMessage, LCXL2OutputMessage, LCXL2Visual, LCXL2Position> for LCXL2Driver {
-  fn connect() -> Result<Self, MidiDriverError> {
-    debug!("LCXL2 -- starting driver...");
-
-    let (midi_in, in_port) = get_in_port("Launch Control XL")?;
-    let (midi_out, out_port) = get_out_port("Launch Control XL")?;
+impl LCXL2Driver {
+  /// partial name of the device's ports
+  pub const PORT: &'static str = "Launch Control XL";
 
-    let conn_out = midi_out
-      .connect(&out_port, "LCXL2 MIDI output writer")
-      .map_err(|e| MidiDriverError::MidirConnectOutputError(e.to_string()))?;
+  /// a driver without a device, its inputs come from the returned sender
+  pub fn connect_virtual() -> Result<(Self, mpsc::Sender<LCXL2InputMessage>), MidiDriverError> {
+    debug!("LCXL2 -- starting virtual driver...");
 
     let (tx, rx) = mpsc::channel::<LCXL2InputMessage>();
+    Ok((
+      Self::start(Connection::Virtual, InputHook::default(), rx)?,
+      tx,
+    ))
+  }
 
-    let _conn_in = midi_in
-      .connect(
-        &in_port,
-        "input-reader",
-        move |_, raw_message, _| {
-          if let Some(message) = LCXL2InputMessage::from_raw(raw_message) {
-            let _ = tx.send(message);
-          }
-        },
-        (),
-      )
-      .map_err(|e| MidiDriverError::MidirConnectInputError(e.to_string()))?;
+  /// calls a function from the midi thread whenever input arrives, to wake up the thread reading it
+  pub fn on_input(&self, f: impl Fn() + Send + 'static) {
+    self.on_input.set(f);
+  }
+
+  /// how a position is lit on the device, as last pushed
+  pub fn get_visual(&self, pos: LCXL2Position) -> Option<&LCXL2Visual> {
+    self.effective_visual.get(&pos.to_raw().ok()?)
+  }
 
+  fn start(
+    connection: Connection,
+    on_input: InputHook,
+    rx: mpsc::Receiver<LCXL2InputMessage>,
+  ) -> Result<Self, MidiDriverError> {
     let mut physical_states = HashMap::new();
     for r in 0..56 {
       if LCXL2Position::is_analog_raw(&r) {
@@ -59,8 +62,8 @@ impl MidiDriver<LCXL2InputMessage, LCXL2OutputMessage, LCXL2Visual, LCXL2Positio
 
     let mut s = Self {
       rx,
-      _conn_in,
-      conn_out,
+      connection,
+      on_input,
       effective_visual: HashMap::new(),
       queued_visual_changes: HashMap::new(),
       physical_states,
@@ -72,6 +75,39 @@ impl MidiDriver<LCXL2InputMessage, LCXL2OutputMessage, LCXL2Visual, LCXL2Positio
 
     Ok(s)
   }
+}
+
+impl MidiDriver<LCXL2InputMessage, LCXL2OutputMessage, LCXL2Visual, LCXL2Position> for LCXL2Driver {
+  fn connect() -> Result<Self, MidiDriverError> {
+    debug!("LCXL2 -- starting driver...");
+
+    let (midi_in, in_port) = get_in_port(Self::PORT)?;
+    let (midi_out, out_port) = get_out_port(Self::PORT)?;
+
+    let conn_out = midi_out
+      .connect(&out_port, "LCXL2 MIDI output writer")
+      .map_err(|e| MidiDriverError::MidirConnectOutputError(e.to_string()))?;
+
+    let (tx, rx) = mpsc::channel::<LCXL2InputMessage>();
+    let on_input = InputHook::default();
+    let hook = on_input.clone();
+
+    let _conn_in = midi_in
+      .connect(
+        &in_port,
+        "input-reader",
+        move |_, raw_message, _| {
+          if let Some(message) = LCXL2InputMessage::from_raw(raw_message) {
+            let _ = tx.send(message);
+            hook.call();
+          }
+        },
+        (),
+      )
+      .map_err(|e| MidiDriverError::MidirConnectInputError(e.to_string()))?;
+
+    Self::start(Connection::Midi { _conn_in, conn_out }, on_input, rx)
+  }
 
   fn close(&mut self) -> Result<(), MidiDriverError> {
     debug!("LCXL2 -- closing driver...");
@@ -140,10 +176,7 @@ impl MidiDriver<LCXL2InputMessage, LCXL2OutputMessage, LCXL2Visual, LCXL2Positio
 
   fn send(&mut self, msg: LCXL2OutputMessage) -> Result<(), MidiDriverError> {
     trace!("LCXL2 -- sending message: {:?}", msg);
-    self
-      .conn_out
-      .send(&msg.to_raw()?)
-      .map_err(MidiDriverError::MidirSendError)
+    self.connection.send(&msg.to_raw()?)
   }
 
   fn pop(&mut self) {
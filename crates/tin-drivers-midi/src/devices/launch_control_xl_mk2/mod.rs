@@ -3,6 +3,7 @@ use crate::{MidiDriverError, MidiPhysicalPosition};
 pub mod driver;
 pub mod input;
 pub mod output;
+pub mod surface;
 pub mod visual;
 
 pub use driver::LCXL2Driver;
@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use crate::devices::launch_control_xl_mk2::{
+  LCXL2Driver, LCXL2InputMessage, LCXL2Position, LCXL2Visual,
+};
+use crate::surface::{FaderButton, FaderControl, FaderEvent, FaderSurface, Light};
+use crate::{MidiDriver, MidiDriverError, MidiPhysicalPosition, MidiPhysicalState};
+
+fn button_position(button: FaderButton) -> Option<LCXL2Position> {
+  match button {
+    FaderButton::Track(x, y) if (1..=8).contains(&x) && (1..=2).contains(&y) => {
+      Some(LCXL2Position::Bottom(x, y))
+    }
+    FaderButton::Track(_, _) => None,
+    FaderButton::Up => Some(LCXL2Position::Up),
+    FaderButton::Down => Some(LCXL2Position::Down),
+    FaderButton::Left => Some(LCXL2Position::Left),
+    FaderButton::Right => Some(LCXL2Position::Right),
+    FaderButton::Device => Some(LCXL2Position::Device),
+    FaderButton::Mute => Some(LCXL2Position::Mute),
+    FaderButton::Solo => Some(LCXL2Position::Solo),
+    FaderButton::Record => Some(LCXL2Position::Record),
+  }
+}
+
+fn control_position(control: FaderControl) -> Option<LCXL2Position> {
+  match control {
+    FaderControl::Knob(x, y) if (1..=8).contains(&x) && (1..=3).contains(&y) => {
+      Some(LCXL2Position::Knob(x, y))
+    }
+    FaderControl::Slider(x) if (1..=8).contains(&x) => Some(LCXL2Position::Slider(x)),
+    _ => None,
+  }
+}
+
+fn button(position: &LCXL2Position) -> Option<FaderButton> {
+  match position.to_raw().ok()? {
+    raw @ 24..=39 => Some(FaderButton::Track((raw - 24) % 8 + 1, (raw - 24) / 8 + 1)),
+    40 => Some(FaderButton::Device),
+    41 => Some(FaderButton::Mute),
+    42 => Some(FaderButton::Solo),
+    43 => Some(FaderButton::Record),
+    44 => Some(FaderButton::Up),
+    45 => Some(FaderButton::Down),
+    46 => Some(FaderButton::Left),
+    47 => Some(FaderButton::Right),
+    _ => None,
+  }
+}
+
+fn control(position: &LCXL2Position) -> Option<FaderControl> {
+  match position.to_raw().ok()? {
+    raw @ 0..=23 => Some(FaderControl::Knob(raw % 8 + 1, raw / 8 + 1)),
+    raw @ 48..=55 => Some(FaderControl::Slider(raw - 47)),
+    _ => None,
+  }
+}
+
+/// lights have 4 levels of red and green, amber in between
+fn visual(position: LCXL2Position, light: Light) -> LCXL2Visual {
+  let level = |v: u8| ((v as u16 * 3 + 63) / 127) as u8;
+  match light {
+    Light::Off => LCXL2Visual::Off(position),
+    Light::Rgb(r, g, _) => LCXL2Visual::Static(position, level(r), level(g)),
+    Light::Palette(0) => LCXL2Visual::Off(position),
+    Light::Palette(_) | Light::Flashing(_, _) | Light::Pulsing(_) => {
+      LCXL2Visual::Static(position, 3, 3)
+    }
+  }
+}
+
+impl FaderSurface for LCXL2Driver {
+  fn knobs(&self) -> (u8, u8) {
+    (8, 3)
+  }
+
+  fn sliders(&self) -> u8 {
+    8
+  }
+
+  fn has_rgb(&self) -> bool {
+    false
+  }
+
+  fn read_events(&mut self) -> Result<VecDeque<FaderEvent>, MidiDriverError> {
+    Ok(
+      self
+        .read()?
+        .into_iter()
+        .filter_map(|message| match message {
+          LCXL2InputMessage::KeyPressed(p) => button(&p).map(FaderEvent::Pressed),
+          LCXL2InputMessage::KeyReleased(p) => button(&p).map(FaderEvent::Released),
+          LCXL2InputMessage::Analog(p, v) => control(&p).map(|c| FaderEvent::Moved(c, v)),
+        })
+        .collect(),
+    )
+  }
+
+  fn is_held(&self, button: FaderButton) -> bool {
+    button_position(button)
+      .and_then(|p| self.get_position_state(p).ok())
+      .is_some_and(|s| matches!(s, MidiPhysicalState::Binary(true)))
+  }
+
+  fn value(&self, control: FaderControl) -> Option<u8> {
+    match self.get_position_state(control_position(control)?).ok()? {
+      MidiPhysicalState::Analog8(v) => Some(v),
+      MidiPhysicalState::Binary(_) => None,
+    }
+  }
+
+  fn light_knob(&mut self, x: u8, y: u8, light: Light) -> Result<(), MidiDriverError> {
+    match control_position(FaderControl::Knob(x, y)) {
+      Some(p) => self.add(visual(p, light)),
+      None => Ok(()),
+    }
+  }
+
+  fn light_button(&mut self, button: FaderButton, light: Light) -> Result<(), MidiDriverError> {
+    match button_position(button) {
+      Some(p) => self.add(visual(p, light)),
+      None => Ok(()),
+    }
+  }
+}
@@ -26,3 +26,13 @@ impl MidiVisual for LCXL2Visual {
     self == absorber
   }
 }
+
+impl LCXL2Visual {
+  /// rgb from 0 to 127 the position is lit with
+  pub fn to_rgb(&self) -> (u8, u8, u8) {
+    match self {
+      Self::Off(_) => (0, 0, 0),
+      Self::Static(_, r, g) => ((*r).min(3) * 42, (*g).min(3) * 42, 0),
+    }
+  }
+}
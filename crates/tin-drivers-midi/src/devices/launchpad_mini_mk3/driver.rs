@@ -2,18 +2,17 @@ use crate::devices::launchpad_mini_mk3::input::LPM3InputMessage;
 use crate::devices::launchpad_mini_mk3::output::LPM3OutputMessage;
 use crate::devices::launchpad_mini_mk3::visual::LPM3Visual;
 use crate::devices::launchpad_mini_mk3::LPM3Position;
-use crate::devices::{get_in_port, get_out_port};
+use crate::devices::{Connection, InputHook, get_in_port, get_out_port};
 use crate::{
   MidiDriver, MidiDriverError, MidiInputMessage, MidiOutputMessage, MidiPhysicalPosition,
   MidiPhysicalState, MidiVisual,
 };
-use midir::{MidiInputConnection, MidiOutputConnection};
 use std::collections::{HashMap, VecDeque};
 use std::sync::mpsc;
 
 pub struct LPM3Driver {
-  _conn_in: MidiInputConnection<()>,
-  conn_out: MidiOutputConnection,
+  connection: Connection,
+  on_input: InputHook,
   rx: mpsc::Receiver<LPM3InputMessage>,
 
   effective_visual: HashMap<u8, LPM3Visual>,
@@ -22,32 +21,36 @@ pub struct LPM3Driver {
   physical_states: HashMap<u8, MidiPhysicalState>,
 }
 
-impl MidiDriver<LPM3InputMessage, LPM3OutputMessage, LPM3Visual, LPM3Position> for LPM3Driver {
-  fn connect() -> Result<Self, MidiDriverError> {
-    debug!("LPM3 -- starting driver...");
-
-    let (midi_in, in_port) = get_in_port("LPMiniMK3 MI")?;
-    let (midi_out, out_port) = get_out_port("LPMiniMK3 MI")?;
+impl LPM3Driver {
+  /// partial name of the device's ports
+  pub const PORT: &'static str = "LPMiniMK3 MI";
 
-    let conn_out = midi_out
-      .connect(&out_port, "LPMiniMK3 MIDI output writer")
-      .map_err(|e| MidiDriverError::MidirConnectOutputError(e.to_string()))?;
+  /// a driver without a device, its inputs come from the returned sender
+  pub fn connect_virtual() -> Result<(Self, mpsc::Sender<LPM3InputMessage>), MidiDriverError> {
+    debug!("LPM3 -- starting virtual driver...");
 
     let (tx, rx) = mpsc::channel::<LPM3InputMessage>();
+    Ok((
+      Self::start(Connection::Virtual, InputHook::default(), rx)?,
+      tx,
+    ))
+  }
 
-    let _conn_in = midi_in
-      .connect(
-        &in_port,
-        "input-reader",
-        move |_, raw_message, _| {
-          if let Some(message) = LPM3InputMessage::from_raw(raw_message) {
-            let _ = tx.send(message);
-          }
-        },
-        (),
-      )
-      .map_err(|e| MidiDriverError::MidirConnectInputError(e.to_string()))?;
+  /// calls a function from the midi thread whenever input arrives, to wake up the thread reading it
+  pub fn on_input(&self, f: impl Fn() + Send + 'static) {
+    self.on_input.set(f);
+  }
+
+  /// how a position is lit on the device, as last pushed
+  pub fn get_visual(&self, pos: LPM3Position) -> Option<&LPM3Visual> {
+    self.effective_visual.get(&pos.to_raw().ok()?)
+  }
 
+  fn start(
+    connection: Connection,
+    on_input: InputHook,
+    rx: mpsc::Receiver<LPM3InputMessage>,
+  ) -> Result<Self, MidiDriverError> {
     let mut physical_states = HashMap::new();
     for y in 1..=9 {
       for x in 1..=9 {
@@ -59,8 +62,8 @@ impl MidiDriver<LPM3InputMessage, LPM3OutputMessage, LPM3Visual, LPM3Position> f
 
     let mut s = Self {
       rx,
-      _conn_in,
-      conn_out,
+      connection,
+      on_input,
       effective_visual: HashMap::new(),
       queued_visual_changes: HashMap::new(),
       physical_states,
@@ -77,6 +80,39 @@ impl MidiDriver<LPM3InputMessage, LPM3OutputMessage, LPM3Visual, LPM3Position> f
 
     Ok(s)
   }
+}
+
+impl MidiDriver<LPM3InputMessage, LPM3OutputMessage, LPM3Visual, LPM3Position> for LPM3Driver {
+  fn connect() -> Result<Self, MidiDriverError> {
+    debug!("LPM3 -- starting driver...");
+
+    let (midi_in, in_port) = get_in_port(Self::PORT)?;
+    let (midi_out, out_port) = get_out_port(Self::PORT)?;
+
+    let conn_out = midi_out
+      .connect(&out_port, "LPMiniMK3 MIDI output writer")
+      .map_err(|e| MidiDriverError::MidirConnectOutputError(e.to_string()))?;
+
+    let (tx, rx) = mpsc::channel::<LPM3InputMessage>();
+    let on_input = InputHook::default();
+    let hook = on_input.clone();
+
+    let _conn_in = midi_in
+      .connect(
+        &in_port,
+        "input-reader",
+        move |_, raw_message, _| {
+          if let Some(message) = LPM3InputMessage::from_raw(raw_message) {
+            let _ = tx.send(message);
+            hook.call();
+          }
+        },
+        (),
+      )
+      .map_err(|e| MidiDriverError::MidirConnectInputError(e.to_string()))?;
+
+    Self::start(Connection::Midi { _conn_in, conn_out }, on_input, rx)
+  }
 
   fn close(&mut self) -> Result<(), MidiDriverError> {
     debug!("LPM3 -- closing driver...");
@@ -124,10 +160,7 @@ impl MidiDriver<LPM3InputMessage, LPM3OutputMessage, LPM3Visual, LPM3Position> f
 
   fn send(&mut self, msg: LPM3OutputMessage) -> Result<(), MidiDriverError> {
     trace!("LPM3 -- sending message: {:?}", msg);
-    self
-      .conn_out
-      .send(&msg.to_raw()?)
-      .map_err(MidiDriverError::MidirSendError)
+    self.connection.send(&msg.to_raw()?)
   }
 
   fn pop(&mut self) {
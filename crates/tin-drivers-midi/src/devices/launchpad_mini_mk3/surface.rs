@@ -0,0 +1,85 @@
+use std::collections::VecDeque;
+
+use crate::devices::launchpad_mini_mk3::{LPM3Driver, LPM3InputMessage, LPM3Position, LPM3Visual};
+use crate::surface::{GridButton, GridEvent, GridSurface, Light};
+use crate::{MidiDriver, MidiDriverError, MidiPhysicalPosition, MidiPhysicalState};
+
+/// the right column's last button is the shift
+fn position(button: GridButton) -> Option<LPM3Position> {
+  match button {
+    GridButton::Pad(x, y) if (1..=8).contains(&x) && (1..=8).contains(&y) => {
+      Some(LPM3Position::Grid(x, y))
+    }
+    GridButton::Scene(y) if (1..=7).contains(&y) => Some(LPM3Position::Grid(9, y)),
+    GridButton::Up => Some(LPM3Position::Up),
+    GridButton::Down => Some(LPM3Position::Down),
+    GridButton::Left => Some(LPM3Position::Left),
+    GridButton::Right => Some(LPM3Position::Right),
+    GridButton::Page(1) => Some(LPM3Position::Session),
+    GridButton::Page(2) => Some(LPM3Position::Drums),
+    GridButton::Page(3) => Some(LPM3Position::Keys),
+    GridButton::Page(4) => Some(LPM3Position::User),
+    GridButton::Shift => Some(LPM3Position::SSM),
+    GridButton::Logo => Some(LPM3Position::Logo),
+    _ => None,
+  }
+}
+
+fn button(position: &LPM3Position) -> Option<GridButton> {
+  let raw = position.to_raw().ok()?;
+  let (row, column) = (raw / 10, raw % 10);
+  match (row, column) {
+    (9, 1) => Some(GridButton::Up),
+    (9, 2) => Some(GridButton::Down),
+    (9, 3) => Some(GridButton::Left),
+    (9, 4) => Some(GridButton::Right),
+    (9, 5..=8) => Some(GridButton::Page(column - 4)),
+    (9, 9) => Some(GridButton::Logo),
+    (1, 9) => Some(GridButton::Shift),
+    (2..=8, 9) => Some(GridButton::Scene(9 - row)),
+    (1..=8, 1..=8) => Some(GridButton::Pad(column, 9 - row)),
+    _ => None,
+  }
+}
+
+impl GridSurface for LPM3Driver {
+  fn size(&self) -> (u8, u8) {
+    (8, 8)
+  }
+
+  fn has_rgb(&self) -> bool {
+    true
+  }
+
+  fn read_events(&mut self) -> Result<VecDeque<GridEvent>, MidiDriverError> {
+    Ok(
+      self
+        .read()?
+        .into_iter()
+        .filter_map(|message| match message {
+          LPM3InputMessage::KeyPressed(p) => button(&p).map(GridEvent::Pressed),
+          LPM3InputMessage::KeyReleased(p) => button(&p).map(GridEvent::Released),
+        })
+        .collect(),
+    )
+  }
+
+  fn is_held(&self, button: GridButton) -> bool {
+    position(button)
+      .and_then(|p| self.get_position_state(p).ok())
+      .is_some_and(|s| matches!(s, MidiPhysicalState::Binary(true)))
+  }
+
+  fn light(&mut self, button: GridButton, light: Light) -> Result<(), MidiDriverError> {
+    let Some(p) = position(button) else {
+      return Ok(());
+    };
+    self.add(match light {
+      Light::Off => LPM3Visual::Off(p),
+      Light::Palette(c) => LPM3Visual::Static(p, c),
+      Light::Flashing(a, b) => LPM3Visual::Flashing(p, a, b),
+      Light::Pulsing(c) => LPM3Visual::Pulsing(p, c),
+      Light::Rgb(r, g, b) => LPM3Visual::RGB(p, r, g, b),
+    })
+  }
+}
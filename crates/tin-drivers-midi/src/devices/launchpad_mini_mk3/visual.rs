@@ -35,3 +35,56 @@ impl MidiVisual for LPM3Visual {
     self == absorber
   }
 }
+
+/// hues of the palette's rows of four, from red to pink
+const PALETTE_HUES: [f64; 14] = [
+  0., 25., 55., 80., 120., 140., 160., 180., 200., 215., 240., 270., 300., 330.,
+];
+
+/// rgb from 0 to 127 of a palette color, approximated
+///
+/// past the greys, the palette has rows of four shades of a hue: light, full, dim and dark
+pub fn palette_rgb(color: u8) -> (u8, u8, u8) {
+  let (hue, saturation, value) = match color {
+    0 => return (0, 0, 0),
+    1 => return (30, 30, 30),
+    2 => return (70, 70, 70),
+    3 => return (127, 127, 127),
+    4..=59 => {
+      let hue = PALETTE_HUES[(color as usize - 4) / 4];
+      match (color - 4) % 4 {
+        0 => (hue, 0.5, 1.),
+        1 => (hue, 1., 1.),
+        2 => (hue, 1., 0.35),
+        _ => (hue, 1., 0.15),
+      }
+    }
+    _ => ((color - 60) as f64 * 360. / 68., 1., 0.8),
+  };
+
+  let sector = hue / 60.;
+  let chroma = value * saturation;
+  let x = chroma * (1. - (sector % 2. - 1.).abs());
+  let (r, g, b) = match sector as u8 {
+    0 => (chroma, x, 0.),
+    1 => (x, chroma, 0.),
+    2 => (0., chroma, x),
+    3 => (0., x, chroma),
+    4 => (x, 0., chroma),
+    _ => (chroma, 0., x),
+  };
+  let m = value - chroma;
+  let channel = |c: f64| ((c + m) * 127.).round() as u8;
+  (channel(r), channel(g), channel(b))
+}
+
+impl LPM3Visual {
+  /// rgb from 0 to 127 the position is lit with, flashing ones in their first color
+  pub fn to_rgb(&self) -> (u8, u8, u8) {
+    match self {
+      Self::Off(_) => (0, 0, 0),
+      Self::Static(_, c) | Self::Flashing(_, c, _) | Self::Pulsing(_, c) => palette_rgb(*c),
+      Self::RGB(_, r, g, b) => (*r, *g, *b),
+    }
+  }
+}
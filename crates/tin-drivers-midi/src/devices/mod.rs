@@ -1,11 +1,56 @@
 //! supported devices
 
 use crate::MidiDriverError;
-use midir::{Ignore, MidiInput, MidiInputPort, MidiOutput, MidiOutputPort};
+use midir::{
+  Ignore, MidiInput, MidiInputConnection, MidiInputPort, MidiOutput, MidiOutputConnection,
+  MidiOutputPort,
+};
+use std::sync::{Arc, Mutex};
 
 pub mod launch_control_xl_mk2;
 pub mod launchpad_mini_mk3;
 
+/// where a driver's messages go
+pub(crate) enum Connection {
+  Midi {
+    _conn_in: MidiInputConnection<()>,
+    conn_out: MidiOutputConnection,
+  },
+  /// no device, inputs are sent to the driver by hand and outputs only change its lights
+  Virtual,
+}
+
+impl Connection {
+  pub(crate) fn send(&mut self, raw: &[u8]) -> Result<(), MidiDriverError> {
+    match self {
+      Self::Midi { conn_out, .. } => conn_out.send(raw).map_err(MidiDriverError::MidirSendError),
+      Self::Virtual => Ok(()),
+    }
+  }
+}
+
+type Hook = Box<dyn Fn() + Send>;
+
+/// function called from the midi thread when input arrives
+#[derive(Clone, Default)]
+pub(crate) struct InputHook(Arc<Mutex<Option<Hook>>>);
+
+impl InputHook {
+  pub(crate) fn set(&self, f: impl Fn() + Send + 'static) {
+    if let Ok(mut hook) = self.0.lock() {
+      *hook = Some(Box::new(f));
+    }
+  }
+
+  pub(crate) fn call(&self) {
+    if let Ok(hook) = self.0.lock()
+      && let Some(f) = hook.as_ref()
+    {
+      f();
+    }
+  }
+}
+
 /// returns in port from a device's partial name
 pub fn get_in_port(device: &str) -> Result<(MidiInput, MidiInputPort), MidiDriverError> {
   let mut midi_in = MidiInput::new(&format!("{} tin-driver input", device))
@@ -45,3 +90,8 @@ pub fn get_out_port(device: &str) -> Result<(MidiOutput, MidiOutputPort), MidiDr
   }
   Err(MidiDriverError::MidiNotFound(device.to_string()))
 }
+
+/// whether a device is plugged in, from its partial name
+pub fn is_present(device: &str) -> bool {
+  get_out_port(device).is_ok()
+}
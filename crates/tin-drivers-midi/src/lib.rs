@@ -8,10 +8,12 @@ use std::collections::VecDeque;
 use std::fmt::Debug;
 use thiserror::Error;
 
+pub mod clock;
 pub mod devices;
 #[allow(unused)]
 #[cfg(feature = "examples")]
 pub mod examples;
+pub mod surface;
 
 /// trait for device position transformers
 pub trait MidiPhysicalPosition: Debug {
@@ -0,0 +1,118 @@
+//! control surfaces by what their controls are for, so views don't depend on one device
+//!
+//! grids are pads with buttons around them, like launchpads,
+//! fader surfaces are knobs and sliders with a few buttons, like launch controls
+
+use std::collections::VecDeque;
+
+use crate::MidiDriverError;
+
+/// a button of a grid surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GridButton {
+  /// pad of the grid, from 1,1 at the top left
+  Pad(u8, u8),
+  /// button at the end of a row of pads, for launching it
+  Scene(u8),
+  Up,
+  Down,
+  Left,
+  Right,
+  /// buttons switching between pages, from 1
+  Page(u8),
+  /// held to reach the second function of other buttons
+  Shift,
+  Logo,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridEvent {
+  Pressed(GridButton),
+  Released(GridButton),
+}
+
+/// an analog control of a fader surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FaderControl {
+  /// knob, from 1,1 at the top left
+  Knob(u8, u8),
+  Slider(u8),
+}
+
+/// a button of a fader surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FaderButton {
+  /// button under a slider, from 1,1 at the top left
+  Track(u8, u8),
+  Up,
+  Down,
+  Left,
+  Right,
+  Device,
+  Mute,
+  Solo,
+  Record,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaderEvent {
+  Pressed(FaderButton),
+  Released(FaderButton),
+  Moved(FaderControl, u8),
+}
+
+impl FaderEvent {
+  /// value a control moved to
+  pub fn moved(&self, control: FaderControl) -> Option<u8> {
+    match self {
+      Self::Moved(c, v) if *c == control => Some(*v),
+      _ => None,
+    }
+  }
+}
+
+/// how a control is lit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Light {
+  Off,
+  /// color of novation's palette, approximated by surfaces without it
+  Palette(u8),
+  /// alternates between two palette colors
+  Flashing(u8, u8),
+  Pulsing(u8),
+  /// 0-127 per channel, approximated by surfaces without rgb
+  Rgb(u8, u8, u8),
+}
+
+pub trait GridSurface {
+  /// columns and rows of pads
+  fn size(&self) -> (u8, u8);
+  /// whether lights take any color, or only a few
+  fn has_rgb(&self) -> bool;
+
+  /// reads the buttons pressed and released since the last read
+  fn read_events(&mut self) -> Result<VecDeque<GridEvent>, MidiDriverError>;
+  fn is_held(&self, button: GridButton) -> bool;
+
+  /// lights a button, buttons the surface doesn't have are left out
+  fn light(&mut self, button: GridButton, light: Light) -> Result<(), MidiDriverError>;
+}
+
+pub trait FaderSurface {
+  /// columns and rows of knobs
+  fn knobs(&self) -> (u8, u8);
+  fn sliders(&self) -> u8;
+  /// whether lights take any color, or only a few
+  fn has_rgb(&self) -> bool;
+
+  /// reads the buttons pressed and released, and the controls moved since the last read
+  fn read_events(&mut self) -> Result<VecDeque<FaderEvent>, MidiDriverError>;
+  fn is_held(&self, button: FaderButton) -> bool;
+  /// where a control was last moved to
+  fn value(&self, control: FaderControl) -> Option<u8>;
+
+  /// lights a knob, knobs the surface doesn't have are left out
+  fn light_knob(&mut self, x: u8, y: u8, light: Light) -> Result<(), MidiDriverError>;
+  /// lights a button, buttons the surface doesn't have are left out
+  fn light_button(&mut self, button: FaderButton, light: Light) -> Result<(), MidiDriverError>;
+}
@@ -0,0 +1,85 @@
+use tin_drivers_midi::MidiDriver;
+use tin_drivers_midi::devices::launch_control_xl_mk2::{
+  LCXL2Driver, LCXL2InputMessage, LCXL2Position, LCXL2Visual,
+};
+use tin_drivers_midi::devices::launchpad_mini_mk3::{
+  LPM3Driver, LPM3InputMessage, LPM3Position, LPM3Visual,
+};
+use tin_drivers_midi::surface::{
+  FaderButton, FaderControl, FaderEvent, FaderSurface, GridButton, GridEvent, GridSurface, Light,
+};
+
+#[test]
+fn virtual_grid() {
+  let (mut driver, input) = LPM3Driver::connect_virtual().unwrap();
+
+  input
+    .send(LPM3InputMessage::KeyPressed(LPM3Position::SSM))
+    .unwrap();
+  input
+    .send(LPM3InputMessage::KeyPressed(LPM3Position::Grid(9, 2)))
+    .unwrap();
+  input
+    .send(LPM3InputMessage::KeyReleased(LPM3Position::Grid(9, 2)))
+    .unwrap();
+  let events: Vec<GridEvent> = driver.read_events().unwrap().into();
+  assert_eq!(
+    events,
+    vec![
+      GridEvent::Pressed(GridButton::Shift),
+      GridEvent::Pressed(GridButton::Scene(2)),
+      GridEvent::Released(GridButton::Scene(2)),
+    ]
+  );
+  assert!(driver.is_held(GridButton::Shift));
+  assert!(!driver.is_held(GridButton::Scene(2)));
+
+  driver.clear().unwrap();
+  driver
+    .light(GridButton::Pad(3, 1), Light::Rgb(127, 0, 0))
+    .unwrap();
+  driver.push().unwrap();
+  assert_eq!(
+    driver.get_visual(LPM3Position::Grid(3, 1)),
+    Some(&LPM3Visual::RGB(LPM3Position::Grid(3, 1), 127, 0, 0))
+  );
+  assert_eq!(
+    driver.get_visual(LPM3Position::Grid(1, 1)),
+    Some(&LPM3Visual::Off(LPM3Position::Raw(81)))
+  );
+}
+
+#[test]
+fn virtual_faders() {
+  let (mut driver, input) = LCXL2Driver::connect_virtual().unwrap();
+
+  input
+    .send(LCXL2InputMessage::Analog(LCXL2Position::Knob(8, 1), 20))
+    .unwrap();
+  input
+    .send(LCXL2InputMessage::Analog(LCXL2Position::Knob(8, 1), 100))
+    .unwrap();
+  input
+    .send(LCXL2InputMessage::KeyPressed(LCXL2Position::Bottom(2, 1)))
+    .unwrap();
+  let events: Vec<FaderEvent> = driver.read_events().unwrap().into();
+  assert_eq!(
+    events,
+    vec![
+      FaderEvent::Pressed(FaderButton::Track(2, 1)),
+      FaderEvent::Moved(FaderControl::Knob(8, 1), 100),
+    ]
+  );
+  assert_eq!(driver.value(FaderControl::Knob(8, 1)), Some(100));
+  assert!(driver.is_held(FaderButton::Track(2, 1)));
+
+  driver.clear().unwrap();
+  driver
+    .light_button(FaderButton::Device, Light::Rgb(127, 127, 0))
+    .unwrap();
+  driver.push().unwrap();
+  assert_eq!(
+    driver.get_visual(LCXL2Position::Device),
+    Some(&LCXL2Visual::Static(LCXL2Position::Device, 3, 3))
+  );
+}
@@ -0,0 +1,101 @@
+//! midi clock shared with other devices, like drum machines
+
+use sophixer_core::{data::quantization::Quantization, messages::renoise::MessageToRenoise};
+use std::time::Instant;
+use tin_drivers_midi::clock::{ClockMessage, MidiClockReceiver, MidiClockSender};
+
+use crate::model::TinModel;
+
+/// bpm steps a followed clock is rounded to, its ticks jitter
+const FOLLOWED_BPM_STEP: f64 = 0.1;
+
+pub enum ClockSync {
+  /// the tempo and renoise's transport go out as midi clock
+  Send {
+    sender: MidiClockSender,
+    bpm: f64,
+    playing: bool,
+  },
+  /// the tempo and renoise's transport follow another device's clock, song positions included
+  Follow { receiver: MidiClockReceiver },
+}
+
+impl ClockSync {
+  pub fn send(sender: MidiClockSender) -> Self {
+    Self::Send {
+      sender,
+      bpm: 0.,
+      playing: false,
+    }
+  }
+
+  pub fn follow(receiver: MidiClockReceiver) -> Self {
+    Self::Follow { receiver }
+  }
+
+  pub fn update(&mut self, tin: &mut TinModel) {
+    match self {
+      Self::Send {
+        sender,
+        bpm,
+        playing,
+      } => {
+        let current = tin.tempo.current(Instant::now());
+        if (current - *bpm).abs() > 0.01 {
+          sender.set_bpm(current);
+          *bpm = current;
+        }
+
+        let transport = tin.transport.as_ref().filter(|t| t.playing);
+        if transport.is_some() != *playing {
+          *playing = transport.is_some();
+          match transport {
+            Some(transport) => {
+              // song positions count sixteenth notes
+              let position = (transport.beats * 4.).floor().clamp(0., 16383.) as u16;
+              if position == 0 {
+                sender.send(ClockMessage::Start);
+              } else {
+                sender.send(ClockMessage::SongPosition(position));
+                sender.send(ClockMessage::Continue);
+              }
+            }
+            None => sender.send(ClockMessage::Stop),
+          }
+        }
+      }
+      Self::Follow { receiver } => {
+        for message in receiver.read() {
+          match message {
+            // renoise plays its own patterns, from where it is
+            ClockMessage::Start | ClockMessage::Continue => tin
+              .scheduler
+              .schedule(Quantization::Off, vec![MessageToRenoise::StartTransport]),
+            ClockMessage::Stop => tin
+              .scheduler
+              .schedule(Quantization::Off, vec![MessageToRenoise::StopTransport]),
+            // song positions count sixteenth notes
+            ClockMessage::SongPosition(position) => tin.scheduler.schedule(
+              Quantization::Off,
+              vec![MessageToRenoise::SetPlaybackBeats(position as f64 / 4.)],
+            ),
+            ClockMessage::Tick => {}
+          }
+        }
+
+        if let Some(bpm) = receiver.bpm() {
+          let bpm = (bpm / FOLLOWED_BPM_STEP).round() * FOLLOWED_BPM_STEP;
+          if (bpm - tin.tempo.target).abs() > FOLLOWED_BPM_STEP / 2. {
+            tin.tempo.set(bpm);
+          }
+        }
+      }
+    }
+  }
+
+  pub fn close(&mut self) {
+    if let Self::Send { sender, .. } = self {
+      sender.close();
+    }
+  }
+}
@@ -0,0 +1,179 @@
+//! pace of the main loop
+//!
+//! frames run at a tick rate, and right away when input arrives from a device or renoise,
+//! so inputs don't wait for the next tick and the loop sleeps when nothing happens
+
+use std::{
+  sync::{Arc, Mutex},
+  thread::{self, Thread},
+  time::{Duration, Instant},
+};
+
+pub const DEFAULT_TICK_RATE: f64 = 100.;
+
+/// time metrics are gathered over
+const METRICS_WINDOW: Duration = Duration::from_secs(1);
+
+/// wakes the main loop up from other threads
+#[derive(Clone)]
+pub struct Wakeup {
+  thread: Thread,
+  /// when the oldest input not handled yet arrived
+  input_since: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Wakeup {
+  /// wakes the current thread up
+  pub fn current() -> Self {
+    Self {
+      thread: thread::current(),
+      input_since: Arc::new(Mutex::new(None)),
+    }
+  }
+
+  pub fn wake(&self) {
+    self.thread.unpark();
+  }
+
+  /// a function waking the loop up for some input, and noting when it arrived
+  pub fn hook(&self) -> impl Fn() + Send + 'static {
+    let wakeup = self.clone();
+    move || {
+      if let Ok(mut since) = wakeup.input_since.lock() {
+        since.get_or_insert_with(Instant::now);
+      }
+      wakeup.wake();
+    }
+  }
+
+  fn take_input(&self) -> Option<Instant> {
+    self
+      .input_since
+      .lock()
+      .ok()
+      .and_then(|mut since| since.take())
+  }
+}
+
+/// how long frames take, and how long inputs wait for their output
+#[derive(Default, Clone)]
+pub struct FrameMetrics {
+  pub frames_per_second: f64,
+  pub frame_average: Duration,
+  pub frame_max: Duration,
+  /// from an input's arrival to the end of the frame handling it, none without inputs
+  pub latency_average: Option<Duration>,
+  pub latency_max: Option<Duration>,
+}
+
+#[derive(Default)]
+struct Window {
+  frames: u32,
+  frame_total: Duration,
+  frame_max: Duration,
+  inputs: u32,
+  latency_total: Duration,
+  latency_max: Duration,
+}
+
+pub struct FrameClock {
+  interval: Duration,
+  wakeup: Wakeup,
+
+  frame_start: Instant,
+  /// start of the previous frame
+  previous_start: Instant,
+  input_since: Option<Instant>,
+
+  window_start: Instant,
+  window: Window,
+  metrics: FrameMetrics,
+}
+
+impl FrameClock {
+  pub fn new(tick_rate: f64, wakeup: Wakeup) -> Self {
+    let now = Instant::now();
+    Self {
+      interval: Duration::from_secs_f64(1. / tick_rate),
+      wakeup,
+      frame_start: now,
+      previous_start: now,
+      input_since: None,
+      window_start: now,
+      window: Window::default(),
+      metrics: FrameMetrics::default(),
+    }
+  }
+
+  /// starts a frame, returns when it started
+  pub fn begin(&mut self) -> Instant {
+    self.previous_start = self.frame_start;
+    self.frame_start = Instant::now();
+    self.input_since = self.wakeup.take_input();
+    self.frame_start
+  }
+
+  /// time since the previous frame started
+  pub fn delta(&self) -> Duration {
+    self.frame_start - self.previous_start
+  }
+
+  /// ends a frame once its output went out, and sleeps until the next tick or input
+  pub fn end(&mut self) {
+    let now = Instant::now();
+
+    let frame = now - self.frame_start;
+    self.window.frames += 1;
+    self.window.frame_total += frame;
+    self.window.frame_max = self.window.frame_max.max(frame);
+    if let Some(since) = self.input_since.take() {
+      let latency = now - since;
+      self.window.inputs += 1;
+      self.window.latency_total += latency;
+      self.window.latency_max = self.window.latency_max.max(latency);
+    }
+    if now - self.window_start >= METRICS_WINDOW {
+      self.publish(now);
+    }
+
+    let next = self.frame_start + self.interval;
+    if now < next {
+      // inputs unpark the thread, and so does anything that arrived during the frame
+      thread::park_timeout(next - now);
+    }
+  }
+
+  pub fn metrics(&self) -> &FrameMetrics {
+    &self.metrics
+  }
+
+  fn publish(&mut self, now: Instant) {
+    let w = std::mem::take(&mut self.window);
+    let elapsed = (now - self.window_start).as_secs_f64();
+    self.window_start = now;
+
+    self.metrics = FrameMetrics {
+      frames_per_second: w.frames as f64 / elapsed,
+      frame_average: w.frame_total / w.frames.max(1),
+      frame_max: w.frame_max,
+      latency_average: (w.inputs > 0).then(|| w.latency_total / w.inputs),
+      latency_max: (w.inputs > 0).then_some(w.latency_max),
+    };
+    trace!(
+      "{:.0} frames per second, {:.1?} per frame, {:.1?} at most",
+      self.metrics.frames_per_second, self.metrics.frame_average, self.metrics.frame_max
+    );
+    if let (Some(average), Some(max)) = (self.metrics.latency_average, self.metrics.latency_max) {
+      trace!(
+        "{} inputs out after {:.1?}, {:.1?} at most",
+        w.inputs, average, max
+      );
+    }
+    if w.frame_max > self.interval * 4 {
+      debug!(
+        "a frame took {:?}, over 4 ticks of {:?}",
+        w.frame_max, self.interval
+      );
+    }
+  }
+}
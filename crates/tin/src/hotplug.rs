@@ -0,0 +1,86 @@
+//! control surfaces that can be plugged in and out while tin is running
+//!
+//! ports are scanned every now and then, missing devices are connected once they show up,
+//! and drivers that fail to send are dropped until their device is back
+
+use std::time::{Duration, Instant};
+use tin_drivers_midi::{MidiDriverError, devices::is_present};
+
+/// time between two scans of the midi ports
+const SCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct Plugged<D> {
+  name: &'static str,
+  /// partial name of the device's ports, none for devices that are always there
+  port: Option<&'static str>,
+  driver: Option<D>,
+  last_scan: Option<Instant>,
+}
+
+impl<D> Plugged<D> {
+  pub fn new(name: &'static str, port: &'static str) -> Self {
+    Self {
+      name,
+      port: Some(port),
+      driver: None,
+      last_scan: None,
+    }
+  }
+
+  /// a device that's never scanned for, like a virtual one
+  pub fn always(name: &'static str, driver: D) -> Self {
+    Self {
+      name,
+      port: None,
+      driver: Some(driver),
+      last_scan: None,
+    }
+  }
+
+  /// connects the device if it showed up, and drops it if it's gone
+  ///
+  /// a new driver starts from a cleared device, so the views draw all of their state on it again
+  pub fn scan(&mut self, now: Instant, connect: impl FnOnce() -> Result<D, MidiDriverError>) {
+    let Some(port) = self.port else {
+      return;
+    };
+    if self.last_scan.is_some_and(|at| now - at < SCAN_INTERVAL) {
+      return;
+    }
+    let first = self.last_scan.is_none();
+    self.last_scan = Some(now);
+
+    match (&self.driver, is_present(port)) {
+      (None, true) => match connect() {
+        Ok(driver) => {
+          info!("{} connected", self.name);
+          self.driver = Some(driver);
+        }
+        Err(e) => warn!("couldn't connect {}: {}", self.name, e),
+      },
+      (None, false) if first => info!("{} not found, waiting for it", self.name),
+      (Some(_), false) => {
+        warn!("{} disconnected", self.name);
+        self.driver = None;
+      }
+      _ => {}
+    }
+  }
+
+  pub fn driver(&mut self) -> Option<&mut D> {
+    self.driver.as_mut()
+  }
+
+  /// runs something on the driver if there is one, and drops it if it fails
+  pub fn with<T>(&mut self, f: impl FnOnce(&mut D) -> Result<T, MidiDriverError>) -> Option<T> {
+    let driver = self.driver.as_mut()?;
+    match f(driver) {
+      Ok(t) => Some(t),
+      Err(e) => {
+        warn!("lost {}: {}", self.name, e);
+        self.driver = None;
+        None
+      }
+    }
+  }
+}
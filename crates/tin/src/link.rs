@@ -0,0 +1,166 @@
+//! tempo and beat phase shared with other tins of the network, over udp multicast
+//!
+//! peers share a timeline: the bpm and the beat it's at.
+//! tempo changes of any peer go to all of them, and every peer follows the furthest timeline,
+//! so a joining peer picks up the session's phase instead of resetting it
+
+use anyhow::Result;
+use intercom::peer::{InterPeer, InterPeerCommunicator, udp::UdpPeer};
+use sophixer_core::messages::link::MessageBetweenTins;
+use std::{
+  collections::HashMap,
+  time::{Duration, Instant},
+};
+
+use crate::model::TinModel;
+
+pub const DEFAULT_GROUP: &str = "239.255.77.77:3010";
+
+/// how often the timeline goes out when nothing changes
+const BROADCAST_INTERVAL: Duration = Duration::from_millis(250);
+/// shortest time between two broadcasts, for ramps
+const MIN_BROADCAST_INTERVAL: Duration = Duration::from_millis(20);
+/// peers not heard from for this long left
+const PEER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// smallest bpm change worth sharing
+const BPM_EPSILON: f64 = 0.01;
+/// timelines closer than this many beats are in phase, the network adds some jitter
+const PHASE_TOLERANCE: f64 = 0.01;
+
+struct LinkCommunicator {}
+impl InterPeerCommunicator<UdpPeer, MessageBetweenTins, MessageBetweenTins> for LinkCommunicator {}
+
+#[derive(Clone, Copy)]
+struct Timeline {
+  bpm: f64,
+  beat: f64,
+  at: Instant,
+}
+
+impl Timeline {
+  fn beat(&self, now: Instant) -> f64 {
+    self.beat + (now - self.at).as_secs_f64() * self.bpm / 60.
+  }
+
+  /// the same timeline going on at another bpm from now on
+  fn with_bpm(&self, bpm: f64, now: Instant) -> Self {
+    Self {
+      bpm,
+      beat: self.beat(now),
+      at: now,
+    }
+  }
+}
+
+pub struct LinkSession {
+  peer: UdpPeer,
+  id: u64,
+  timeline: Timeline,
+  /// other peers, and when they were last heard from
+  peers: HashMap<u64, Instant>,
+  last_broadcast: Option<Instant>,
+  /// the timeline changed since the last broadcast
+  changed: bool,
+}
+
+impl LinkSession {
+  pub fn join(group: &str, bpm: f64) -> Result<Self> {
+    let peer = UdpPeer::join(group)?;
+    let id = fastrand::u64(..);
+    info!("joined link session on {} as {}", group, id);
+
+    Ok(Self {
+      peer,
+      id,
+      timeline: Timeline {
+        bpm,
+        beat: 0.,
+        at: Instant::now(),
+      },
+      peers: HashMap::new(),
+      last_broadcast: None,
+      changed: true,
+    })
+  }
+
+  pub fn update(&mut self, tin: &mut TinModel) -> Result<()> {
+    let now = Instant::now();
+
+    self.peer.fetch()?;
+    let messages = LinkCommunicator::get_messages(&self.peer).unwrap_or_default();
+    for (_, message) in messages {
+      match message {
+        MessageBetweenTins::Tempo(peer, bpm, beat, _) if peer != self.id => {
+          if self.peers.insert(peer, now).is_none() {
+            info!("peer {} joined the link session", peer);
+          }
+          if (bpm - self.timeline.bpm).abs() > BPM_EPSILON {
+            info!("link session moved to {} bpm", bpm);
+            self.timeline = self.timeline.with_bpm(bpm, now);
+            tin.tempo.set(bpm);
+          }
+          if beat > self.timeline.beat(now) + PHASE_TOLERANCE {
+            self.timeline = Timeline { bpm, beat, at: now };
+          }
+        }
+        MessageBetweenTins::Leave(peer) if self.peers.remove(&peer).is_some() => {
+          info!("peer {} left the link session", peer);
+        }
+        _ => {}
+      }
+    }
+    self.peers.retain(|peer, seen| {
+      let alive = now - *seen < PEER_TIMEOUT;
+      if !alive {
+        info!("lost peer {} of the link session", peer);
+      }
+      alive
+    });
+
+    // tempo changes made here
+    let bpm = tin.tempo.current(now);
+    if (bpm - self.timeline.bpm).abs() > BPM_EPSILON {
+      self.timeline = self.timeline.with_bpm(bpm, now);
+      self.changed = true;
+    }
+
+    let since_broadcast = self.last_broadcast.map(|at| now - at);
+    let broadcast = match since_broadcast {
+      None => true,
+      Some(since) if self.changed => since >= MIN_BROADCAST_INTERVAL,
+      Some(since) => since >= BROADCAST_INTERVAL,
+    };
+    let quantum = self.quantum(tin);
+    if broadcast {
+      LinkCommunicator::broadcast_message(
+        &self.peer,
+        MessageBetweenTins::Tempo(self.id, self.timeline.bpm, self.timeline.beat(now), quantum),
+      )?;
+      self.last_broadcast = Some(now);
+      self.changed = false;
+    }
+
+    tin.scheduler.follow_session(match self.peers.is_empty() {
+      true => None,
+      false => Some((self.timeline.beat(now), quantum)),
+    });
+
+    Ok(())
+  }
+
+  /// beats per bar, as renoise has them
+  fn quantum(&self, tin: &TinModel) -> f64 {
+    tin
+      .transport
+      .as_ref()
+      .map(|t| t.beats_per_bar.max(1) as f64)
+      .unwrap_or(4.)
+  }
+
+  pub fn leave(self) -> Result<()> {
+    LinkCommunicator::broadcast_message(&self.peer, MessageBetweenTins::Leave(self.id))?;
+    self.peer.leave()?;
+    Ok(())
+  }
+}
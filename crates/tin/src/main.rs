@@ -1,124 +1,410 @@
 #[macro_use]
 extern crate log;
 extern crate pretty_env_logger;
+mod clock;
+mod frames;
+mod hotplug;
+mod link;
 mod model;
+mod rehearsal;
+mod scheduler;
+mod scripting;
 mod servers;
+mod snapshot;
+mod tempo;
+mod tui;
 mod views;
 
+use crate::clock::ClockSync;
+use crate::frames::{DEFAULT_TICK_RATE, FrameClock, Wakeup};
+use crate::hotplug::Plugged;
+use crate::link::{DEFAULT_GROUP, LinkSession};
 use crate::model::{LPM3View, TinModel};
+use crate::rehearsal::{Rehearsal, RehearsalStatus, VirtualInputs};
+use crate::scripting::Scripts;
 use crate::servers::renoise::RenoiseCommunicator;
+use crate::snapshot::{SNAPSHOT_INTERVAL, Snapshot};
+use crate::tui::{Dashboard, LogBuffer};
 use crate::views::lcxl2_control::ViewLCXL2Control;
 use crate::views::lpm3_matrix::ViewLPM3Matrix;
 use crate::views::lpm3_songlist::ViewLPM3SongList;
+use crate::views::{FaderDevice, GridDevice, ViewStack};
 use anyhow::Result;
-use argparse::{ArgumentParser, Store};
+use argparse::{ArgumentParser, Store, StoreOption, StoreTrue};
 use intercom::server::InterServer;
 use intercom::server::udp::UdpServer;
-use std::fs::read_to_string;
+use pretty_env_logger::env_logger::{Target, WriteStyle};
+use sophixer_core::data::{Set, migrations, validation::Severity};
+use std::fs::{metadata, read_to_string};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime};
 use tin_drivers_midi::MidiDriver;
+use tin_drivers_midi::clock::{MidiClockReceiver, MidiClockSender};
 use tin_drivers_midi::devices::launch_control_xl_mk2::LCXL2Driver;
 use tin_drivers_midi::devices::launchpad_mini_mk3::LPM3Driver;
+use tin_drivers_midi::surface::{FaderSurface, GridSurface};
 
-fn main() -> Result<()> {
-  pretty_env_logger::init();
+/// how often the set file is checked for changes
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// logs the set's diagnostics, and refuses sets with errors
+fn check_set(set: &Set) -> Result<()> {
+  let diagnostics = set.validate();
+  for d in &diagnostics {
+    match d.severity {
+      Severity::Warning => warn!("{}", d),
+      Severity::Error => error!("{}", d),
+    }
+  }
+
+  let errors = diagnostics
+    .iter()
+    .filter(|d| d.severity == Severity::Error)
+    .count();
+  if errors > 0 {
+    return Err(anyhow::Error::msg(format!("set has {} error(s)", errors)));
+  }
+  Ok(())
+}
+
+/// reads, migrates and checks a set file, and compiles the scripts of its buttons
+fn load_set(set_file: &str) -> Result<(Set, Scripts)> {
+  let set_string = read_to_string(set_file)?;
+  let version = migrations::read_version(&set_string)?;
+  if version < migrations::CURRENT_VERSION {
+    info!(
+      "set was written for version {}, migrating it to version {}",
+      version,
+      migrations::CURRENT_VERSION
+    );
+  }
+  let set = Set::load(&set_string)?;
+  check_set(&set)?;
+  let scripts = Scripts::load(&set, Some(set_file))?;
+  Ok((set, scripts))
+}
+
+fn modified(set_file: &str) -> Option<SystemTime> {
+  metadata(set_file).and_then(|m| m.modified()).ok()
+}
 
+fn main() -> Result<()> {
   let mut set_file = ".".to_string();
+  let mut resume = false;
+  let mut clock_out: Option<String> = None;
+  let mut clock_in: Option<String> = None;
+  let mut link = false;
+  let mut link_group = DEFAULT_GROUP.to_string();
+  let mut virtual_devices = false;
+  let mut rehearsal_file: Option<String> = None;
+  let mut dashboard = false;
+  let mut tick_rate = DEFAULT_TICK_RATE;
   {
     let mut ap = ArgumentParser::new();
     ap.set_description("main server for Sophixer");
     ap.refer(&mut set_file)
       .add_argument("set file", Store, "file of the set in ron notation");
+    ap.refer(&mut resume).add_option(
+      &["--resume"],
+      StoreTrue,
+      "restore the state tin was in when it stopped",
+    );
+    ap.refer(&mut clock_out).add_option(
+      &["--clock-out"],
+      StoreOption,
+      "send midi clock to the port whose name contains this",
+    );
+    ap.refer(&mut clock_in).add_option(
+      &["--clock-in"],
+      StoreOption,
+      "follow the midi clock of the port whose name contains this",
+    );
+    ap.refer(&mut link).add_option(
+      &["--link"],
+      StoreTrue,
+      "share tempo and phase with other tins of the network",
+    );
+    ap.refer(&mut link_group).add_option(
+      &["--link-group"],
+      Store,
+      "multicast address and port of the link session",
+    );
+    ap.refer(&mut virtual_devices).add_option(
+      &["--virtual"],
+      StoreTrue,
+      "run on virtual devices instead of the launchpad and launch control",
+    );
+    ap.refer(&mut rehearsal_file).add_option(
+      &["--rehearsal"],
+      StoreOption,
+      "play inputs from this file on the virtual devices, implies --virtual",
+    );
+    ap.refer(&mut dashboard).add_option(
+      &["--tui"],
+      StoreTrue,
+      "show a dashboard in the terminal, with the logs",
+    );
+    ap.refer(&mut tick_rate).add_option(
+      &["--tick-rate"],
+      Store,
+      "frames per second when nothing happens, inputs are handled right away",
+    );
     ap.parse_args_or_exit();
   }
+
+  let log = LogBuffer::default();
+  match dashboard {
+    true => pretty_env_logger::formatted_builder()
+      .parse_env("RUST_LOG")
+      .target(Target::Pipe(Box::new(log.clone())))
+      .write_style(WriteStyle::Never)
+      .init(),
+    false => pretty_env_logger::init(),
+  }
   trace!("loading set in: {set_file:?}");
 
-  let set_string = read_to_string(set_file)?;
-  let set = ron::from_str(&set_string)?;
+  if tick_rate.is_nan() || tick_rate <= 0. {
+    return Err(anyhow::Error::msg(format!(
+      "tick rate must be above 0, got {}",
+      tick_rate
+    )));
+  }
+
+  let set_modified = modified(&set_file);
+  let (set, scripts) = load_set(&set_file)?;
 
-  let mut tin = TinModel::new(set);
+  let snapshot_file = Snapshot::path(&set_file);
+  let mut tin = TinModel::new(set, scripts);
+  tin.set_file = Some(set_file.clone());
+  tin.set_modified = set_modified;
+
+  if resume {
+    match Snapshot::read(&snapshot_file) {
+      Ok(snapshot) => {
+        snapshot.apply(&mut tin);
+        info!("resumed from {}", snapshot_file);
+      }
+      Err(e) => warn!("couldn't resume from {}: {}", snapshot_file, e),
+    }
+  }
 
   let running = Arc::new(AtomicBool::new(true));
   let r = running.clone();
+  let wakeup = Wakeup::current();
+  let w = wakeup.clone();
 
   ctrlc::set_handler(move || {
     info!("exiting...");
     r.store(false, Ordering::SeqCst);
+    w.wake();
   })?;
 
-  let mut lpm3driver = LPM3Driver::connect()?;
-  let mut lcxl2driver = LCXL2Driver::connect()?;
+  let mut rehearsal = match &rehearsal_file {
+    Some(path) => Some(Rehearsal::load(path)?),
+    None => None,
+  };
+  let (mut lpm3, mut lcxl2, virtual_inputs) = match virtual_devices || rehearsal.is_some() {
+    true => {
+      let (lpm3driver, lpm3_input) = LPM3Driver::connect_virtual()?;
+      let (lcxl2driver, lcxl2_input) = LCXL2Driver::connect_virtual()?;
+      info!("running on virtual devices");
+      (
+        Plugged::always("virtual launchpad mini mk3", lpm3driver),
+        Plugged::always("virtual launch control xl mk2", lcxl2driver),
+        Some(VirtualInputs {
+          lpm3: lpm3_input,
+          lcxl2: lcxl2_input,
+        }),
+      )
+    }
+    false => (
+      Plugged::new("launchpad mini mk3", LPM3Driver::PORT),
+      Plugged::new("launch control xl mk2", LCXL2Driver::PORT),
+      None,
+    ),
+  };
+  let mut rehearsal_failures = 0;
+
+  let mut clock = match (&clock_out, &clock_in) {
+    (Some(_), Some(_)) => {
+      return Err(anyhow::Error::msg(
+        "midi clock can't be sent and followed at once",
+      ));
+    }
+    (Some(port), None) => Some(ClockSync::send(MidiClockSender::connect(
+      port,
+      tin.tempo.target,
+    )?)),
+    (None, Some(port)) => Some(ClockSync::follow(MidiClockReceiver::connect(port)?)),
+    (None, None) => None,
+  };
+
+  let mut link = match link {
+    true => Some(LinkSession::join(&link_group, tin.tempo.target)?),
+    false => None,
+  };
 
   let mut server = UdpServer::start("0.0.0.0:3000")?;
+  server.on_receive(wakeup.hook());
+
+  // the matrix is opened from the songlist
+  let resumed_view = tin.lpm3view;
+  let mut lpm3_views =
+    ViewStack::<GridDevice>::new(Box::new(ViewLPM3SongList::new(&tin)), &mut tin);
+  if resumed_view == LPM3View::Matrix {
+    lpm3_views.push(Box::new(ViewLPM3Matrix::new()), &mut tin);
+  }
+  let mut lcxl2_views =
+    ViewStack::<FaderDevice>::new(Box::new(ViewLCXL2Control::new(&tin)), &mut tin);
 
-  let mut view_lpm3_songlist = ViewLPM3SongList::new(&tin);
-  let mut view_lpm3_matrix = ViewLPM3Matrix::new();
-  let mut view_lcxl2_control = ViewLCXL2Control::new(&tin);
+  let mut dashboard = match dashboard {
+    true => Some(Dashboard::start(log)?),
+    false => None,
+  };
 
-  let mut instant = Instant::now();
+  let mut frames = FrameClock::new(tick_rate, wakeup.clone());
+  let mut last_snapshot = (Instant::now(), Snapshot::take(&tin));
+  let mut last_reload_check = Instant::now();
 
   info!("running...");
   while running.load(Ordering::SeqCst) {
-    let current_time = Instant::now();
-    let delta_time = current_time - instant;
+    let current_time = frames.begin();
+    let delta_time = frames.delta();
 
     server.fetch()?;
     RenoiseCommunicator::update_model(&mut tin, &server)?;
 
-    let lpm3_inputs = lpm3driver.read()?;
-    let lcxl2_inputs = lcxl2driver.read()?;
-
-    match &tin.lpm3view {
-      LPM3View::Matrix => {
-        view_lpm3_matrix.update(
-          &delta_time,
-          &mut tin,
-          &mut lpm3driver,
-          lpm3_inputs.clone(),
-          &server,
-        )?;
-      }
-      LPM3View::SongList => {
-        view_lpm3_songlist.update(
-          &delta_time,
-          &mut tin,
-          &mut lpm3driver,
-          lpm3_inputs.clone(),
-          &server,
-        )?;
+    if let (Some(s), Some(inputs)) = (&mut rehearsal, &virtual_inputs) {
+      match s.update(
+        current_time,
+        inputs,
+        lpm3.driver().as_deref(),
+        lcxl2.driver().as_deref(),
+      ) {
+        RehearsalStatus::Running => {}
+        RehearsalStatus::Done => {
+          info!("rehearsal done");
+          rehearsal = None;
+        }
+        RehearsalStatus::Quit(failures) => {
+          info!("rehearsal done, exiting...");
+          rehearsal_failures = failures;
+          running.store(false, Ordering::SeqCst);
+        }
       }
     }
-    view_lcxl2_control.update(
-      &delta_time,
-      &mut tin,
-      &mut lcxl2driver,
-      lcxl2_inputs.clone(),
-      &server,
-    )?;
-
-    lpm3driver.clear()?;
-    lcxl2driver.clear()?;
-
-    match &tin.lpm3view {
-      LPM3View::Matrix => {
-        view_lpm3_matrix.draw(&tin, &mut lpm3driver)?;
+
+    lpm3.scan(current_time, || {
+      LPM3Driver::connect().inspect(|d| d.on_input(wakeup.hook()))
+    });
+    lcxl2.scan(current_time, || {
+      LCXL2Driver::connect().inspect(|d| d.on_input(wakeup.hook()))
+    });
+
+    if let Some(inputs) = lpm3.with(|d| d.read_events())
+      && let Some(driver) = lpm3.driver()
+    {
+      lpm3_views.update(&delta_time, &mut tin, driver, inputs, &server)?;
+    }
+    if let Some(inputs) = lcxl2.with(|d| d.read_events())
+      && let Some(driver) = lcxl2.driver()
+    {
+      lcxl2_views.update(&delta_time, &mut tin, driver, inputs, &server)?;
+    }
+
+    if let Some(link) = &mut link {
+      link.update(&mut tin)?;
+    }
+    if let Some(clock) = &mut clock {
+      clock.update(&mut tin);
+    }
+
+    tin.update_scripts(current_time);
+    RenoiseCommunicator::send_scheduled(&mut tin, &server)?;
+
+    lpm3.with(|d| d.clear());
+    lcxl2.with(|d| d.clear());
+
+    if let Some(driver) = lpm3.driver() {
+      lpm3_views.draw(&tin, driver)?;
+    }
+    if let Some(driver) = lcxl2.driver() {
+      lcxl2_views.draw(&tin, driver)?;
+    }
+
+    lpm3.with(|d| d.push());
+    lcxl2.with(|d| d.push());
+
+    if let Some(dashboard) = &mut dashboard
+      && !dashboard.update(
+        current_time,
+        &tin,
+        frames.metrics(),
+        lpm3.driver().as_deref(),
+        lcxl2.driver().as_deref(),
+      )?
+    {
+      info!("exiting...");
+      running.store(false, Ordering::SeqCst);
+    }
+
+    // the set file is watched, so sets can be tweaked in the editor while playing
+    if current_time - last_reload_check >= RELOAD_INTERVAL {
+      last_reload_check = current_time;
+      let set_modified = modified(&set_file);
+      // scripts too, they're part of the set
+      if set_modified != tin.set_modified || tin.scripts.changed(Some(&set_file)) {
+        tin.set_modified = set_modified;
+        match load_set(&set_file) {
+          Ok((set, scripts)) => {
+            tin.reload_set(set, scripts);
+            lpm3_views.reload(&tin);
+            lcxl2_views.reload(&tin);
+            if !tin.devices.tracks.is_empty() {
+              for d in tin.set.check_references(&tin.devices) {
+                warn!("{}", d);
+              }
+            }
+            info!("reloaded set");
+          }
+          Err(e) => error!("couldn't reload set, keeping the current one: {}", e),
+        }
       }
-      LPM3View::SongList => {
-        view_lpm3_songlist.draw(&tin, &mut lpm3driver)?;
+    }
+
+    if current_time - last_snapshot.0 >= SNAPSHOT_INTERVAL {
+      let snapshot = Snapshot::take(&tin);
+      // a failed write keeps the old snapshot, so it's tried again on the next interval
+      if snapshot != last_snapshot.1 {
+        match snapshot.write(&snapshot_file) {
+          Ok(()) => last_snapshot.1 = snapshot,
+          Err(e) => warn!("couldn't write state to {}: {}", snapshot_file, e),
+        }
       }
+      last_snapshot.0 = current_time;
     }
-    view_lcxl2_control.draw(&tin, &mut lcxl2driver)?;
 
-    lpm3driver.push()?;
-    lcxl2driver.push()?;
+    frames.end();
+  }
+
+  Snapshot::take(&tin).write(&snapshot_file)?;
 
-    instant = current_time;
+  if let Some(clock) = &mut clock {
+    clock.close();
+  }
+  if let Some(link) = link {
+    link.leave()?;
   }
+  lpm3.with(|d| d.close());
+  lcxl2.with(|d| d.close());
 
-  lpm3driver.close()?;
-  lcxl2driver.close()?;
+  if rehearsal_failures > 0 {
+    return Err(anyhow::Error::msg(format!(
+      "rehearsal expectations failed: {}",
+      rehearsal_failures
+    )));
+  }
 
   Ok(())
 }
@@ -1,43 +1,741 @@
-use sophixer_core::data::{
-  Set,
-  buttons::{ActionDescriptor, SongButtonActionValue},
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sophixer_core::{
+  data::{
+    Scene, Set,
+    buttons::{ActionContext, ActionDescriptor, SongButtonAction, SongButtonActionValue},
+    follow::FollowAction,
+    quantization::Quantization,
+    references::DeviceChains,
+  },
+  messages::renoise::{MessageToRenoise, Transport},
+};
+use std::{
+  collections::HashMap,
+  fs::{metadata, rename, write},
+  net::SocketAddr,
+  time::{Duration, Instant, SystemTime},
+};
+
+use crate::{
+  scheduler::Scheduler,
+  scripting::{ScriptState, Scripts},
+  tempo::Tempo,
 };
-use std::{collections::HashMap, net::SocketAddr};
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LPM3View {
   SongList,
   Matrix,
 }
 
+/// the pattern launched last, see [`TinModel::follow_transport`]
+pub struct PatternPlayback {
+  pub song_id: String,
+  pub y: i64,
+  /// pattern launched before this one in the same song
+  pub previous: Option<i64>,
+  /// completed loops
+  pub loops: u64,
+  /// whether the follow action got scheduled
+  pub followed: bool,
+}
+
+/// a song switch waiting for its transition's grid, see [`TinModel::queued_song`]
+///
+/// tin switches to the song once the transition's messages went out, then waits for renoise to load its file
+pub struct SongSwitch {
+  pub song_id: String,
+  pub position: usize,
+  /// setlist position of the song playing until then
+  pub previous: Option<usize>,
+  /// of the transition's messages in the scheduler
+  id: u64,
+  /// pattern launched once the switch lands, if renoise doesn't schedule it itself
+  pattern: Option<i64>,
+  /// renoise loads the song's file on the grid
+  loads: bool,
+  /// tin switched to the song, renoise is still loading its file
+  landed: bool,
+}
+
 pub struct TinModel {
   pub set: Set,
+  /// where the set gets saved back, when scenes change
+  pub set_file: Option<String>,
+  /// modification time of the set file when it was last read or written
+  pub set_modified: Option<SystemTime>,
 
   pub lpm3view: LPM3View,
+  /// offset of the matrix view over the song's grid
+  pub camera: (i64, i64),
 
   pub renoise_socket: Option<SocketAddr>,
   pub current_song: Option<String>,
 
-  pub bpm: f64,
+  pub tempo: Tempo,
   pub button_states: HashMap<(String, i64, i64), SongButtonActionValue>,
+
+  pub transport: Option<Transport>,
+  pub scheduler: Scheduler,
+
+  /// device chains of the renoise project, reported by Calcium on connect
+  pub devices: DeviceChains,
+  /// whether Calcium finished reporting the device chains, named references can't resolve before
+  pub devices_reported: bool,
+
+  pub pattern: Option<PatternPlayback>,
+  /// position of the current song in the setlist, songs can appear more than once
+  pub setlist_position: Option<usize>,
+  pub song_switch: Option<SongSwitch>,
+  /// last two scenes recalled in the current song, the latest last, see [`TinModel::morph_scenes`]
+  pub recalled_scenes: Vec<usize>,
+
+  /// scripts of the set's script buttons, with what they remember
+  pub scripts: Scripts,
 }
 
-impl TinModel {
-  pub fn new(set: Set) -> Self {
-    let mut button_states = HashMap::new();
+fn default_button_states(set: &Set) -> HashMap<(String, i64, i64), SongButtonActionValue> {
+  let mut button_states = HashMap::new();
 
-    for (song_id, song) in &set.songs {
-      for ((bx, by), button) in &song.buttons {
-        button_states.insert((song_id.clone(), *bx, *by), button.action.get_default());
-      }
+  for (song_id, song) in &set.songs {
+    for ((bx, by), button) in &song.buttons {
+      button_states.insert((song_id.clone(), *bx, *by), button.action.get_default());
     }
+  }
+
+  button_states
+}
+
+impl TinModel {
+  pub fn new(set: Set, scripts: Scripts) -> Self {
+    let button_states = default_button_states(&set);
 
     Self {
       set,
+      set_file: None,
+      set_modified: None,
       lpm3view: LPM3View::SongList,
+      camera: (0, 0),
       renoise_socket: None,
       current_song: None,
-      bpm: 125.,
+      tempo: Tempo::new(125.),
       button_states,
+      transport: None,
+      scheduler: Scheduler::new(),
+      devices: DeviceChains::default(),
+      devices_reported: false,
+      pattern: None,
+      setlist_position: None,
+      song_switch: None,
+      recalled_scenes: Vec::new(),
+      scripts,
+    }
+  }
+
+  /// what a script of a song sees of tin
+  pub fn script_state(&self, now: Instant, song_id: &str) -> ScriptState {
+    let transport = self.transport.as_ref().filter(|t| t.playing);
+    ScriptState {
+      bpm: self.tempo.current(now),
+      song: song_id.to_string(),
+      pattern: self
+        .pattern
+        .as_ref()
+        .filter(|p| p.song_id == song_id)
+        .map(|p| p.y),
+      playing: transport.is_some(),
+      bar: transport.map(|t| t.bar()),
+      beat: transport.map(|t| t.beat()),
+      buttons: self
+        .button_states
+        .iter()
+        .filter(|((s, _, _), _)| s == song_id)
+        .map(|((_, x, y), v)| ((*x, *y), *v))
+        .collect(),
+    }
+  }
+
+  /// fires the timers scripts set, their messages go out right away
+  pub fn update_scripts(&mut self, now: Instant) {
+    for (song_id, pos, function) in self.scripts.due(now) {
+      let state = self.script_state(now, &song_id);
+      let messages = self.scripts.call(&song_id, pos, &function, state, now);
+      self.scheduler.schedule(Quantization::Off, messages);
+    }
+  }
+
+  /// moves a button to its next state, and schedules its messages on the button's grid
+  ///
+  /// switching on a member of an exclusive group switches the other members off on the same grid
+  pub fn trigger_button(&mut self, song_id: &str, pos: (i64, i64)) -> Result<()> {
+    let song = self
+      .set
+      .songs
+      .get(song_id)
+      .ok_or(anyhow::Error::msg("couldn't find song id in songs"))?;
+    let button = song
+      .buttons
+      .get(&pos)
+      .ok_or(anyhow::Error::msg("couldn't find button in set"))?;
+
+    let ctx = ActionContext {
+      layout: &self.set.layout,
+      devices: self.devices_reported.then_some(&self.devices),
+    };
+
+    let key = (song_id.to_string(), pos.0, pos.1);
+    let current_state = self
+      .button_states
+      .get(&key)
+      .ok_or(anyhow::Error::msg("couldn't find state in model"))?;
+    let next = button.action.next(*current_state)?;
+    match button.action.create_timed_renoise_message(next, &ctx) {
+      Ok(messages) => self.scheduler.schedule_timed(button.quantization, messages),
+      Err(e) => warn!("couldn't send button {},{}: {}", pos.0, pos.1, e),
+    }
+    if let SongButtonAction::Script(_) = &button.action {
+      let now = Instant::now();
+      let state = self.script_state(now, song_id);
+      let messages = self.scripts.call(song_id, pos, "press", state, now);
+      self.scheduler.schedule(button.quantization, messages);
+    }
+    self.button_states.insert(key, next);
+
+    if button.action.is_active(next) {
+      for (nx, ny) in song.get_group_neighbours(pos) {
+        let Some(neighbour) = song.buttons.get(&(nx, ny)) else {
+          warn!("group member {},{} of {} has no button", nx, ny, song_id);
+          continue;
+        };
+        let key = (song_id.to_string(), nx, ny);
+        let state = self
+          .button_states
+          .get(&key)
+          .ok_or(anyhow::Error::msg("couldn't find state in model"))?;
+        if neighbour.action.is_active(*state) {
+          let inactive = neighbour.action.get_inactive();
+          match neighbour
+            .action
+            .create_timed_renoise_message(inactive, &ctx)
+          {
+            Ok(messages) => self.scheduler.schedule_timed(button.quantization, messages),
+            Err(e) => warn!("couldn't send group member {},{}: {}", nx, ny, e),
+          }
+          self.button_states.insert(key, inactive);
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// schedules a pattern of a song, and starts counting its loops
+  pub fn launch_pattern(
+    &mut self,
+    song_id: &str,
+    y: i64,
+    quantization: Quantization,
+    force: bool,
+  ) -> Result<()> {
+    let pattern = self
+      .set
+      .songs
+      .get(song_id)
+      .ok_or(anyhow::Error::msg("couldn't find song id in songs"))?
+      .patterns
+      .get(&y)
+      .ok_or(anyhow::Error::msg("couldn't find pattern in song"))?;
+
+    self.scheduler.schedule(
+      quantization,
+      vec![
+        MessageToRenoise::PlaySection(pattern.start, force),
+        MessageToRenoise::SetLoop(pattern.loop_start, pattern.loop_end),
+      ],
+    );
+    trace!(
+      "playing pattern start {} loop_start {} loop_end {}",
+      pattern.start, pattern.loop_start, pattern.loop_end
+    );
+
+    let previous = self
+      .pattern
+      .take()
+      .filter(|p| p.song_id == song_id)
+      .map(|p| p.y);
+    self.pattern = Some(PatternPlayback {
+      song_id: song_id.to_string(),
+      y,
+      previous,
+      loops: 0,
+      followed: false,
+    });
+
+    Ok(())
+  }
+
+  /// counts the loops of the launched pattern, and schedules its follow action on the last one
+  ///
+  /// patterns are followed the way renoise schedules them: at the end of the last loop
+  pub fn follow_transport(&mut self, previous: &Transport, current: &Transport) -> Result<()> {
+    let Some(playback) = &mut self.pattern else {
+      return Ok(());
+    };
+    let Some(pattern) = self
+      .set
+      .songs
+      .get(&playback.song_id)
+      .and_then(|s| s.patterns.get(&playback.y))
+    else {
+      self.pattern = None;
+      return Ok(());
+    };
+    if !current.playing || playback.followed || pattern.loops == 0 {
+      return Ok(());
+    }
+
+    if pattern.has_looped(previous, current) {
+      playback.loops += 1;
+      trace!("pattern {} looped {} time(s)", playback.y, playback.loops);
+    }
+
+    if playback.loops + 1 < pattern.loops || current.sequence != pattern.loop_end {
+      return Ok(());
+    }
+
+    let target = match &pattern.follow {
+      FollowAction::None => return Ok(()),
+      FollowAction::Stop => {
+        playback.followed = true;
+        info!("following pattern {} with a stop", playback.y);
+        self
+          .scheduler
+          .schedule(Quantization::Pattern, vec![MessageToRenoise::StopTransport]);
+        return Ok(());
+      }
+      FollowAction::Goto(y) => Some(*y),
+      FollowAction::Back => playback.previous,
+      FollowAction::Random(ys) => fastrand::choice(ys).copied(),
+      FollowAction::NextSong => {
+        playback.followed = true;
+        info!("following pattern {} with the next song", playback.y);
+        return self.step_song(1);
+      }
+    };
+
+    playback.followed = true;
+    let song_id = playback.song_id.clone();
+    match target {
+      Some(y) => {
+        info!("following pattern {} with pattern {}", playback.y, y);
+        self.launch_pattern(&song_id, y, Quantization::Off, false)?;
+      }
+      None => warn!("pattern {} has nothing to follow with", playback.y),
+    }
+
+    Ok(())
+  }
+
+  /// switches to the song at a position of the setlist, following the song's transition
+  ///
+  /// the current song stays until the transition's grid, see [`Self::land_song_switch`]
+  pub fn switch_song(&mut self, position: usize) -> Result<()> {
+    let setlist = self.set.get_setlist();
+    let song_id = setlist
+      .get(position)
+      .ok_or(anyhow::Error::msg("couldn't find position in setlist"))?
+      .clone();
+    let song = self
+      .set
+      .songs
+      .get(&song_id)
+      .ok_or(anyhow::Error::msg("couldn't find song id in songs"))?;
+    let transition = song.transition.clone();
+
+    // a switch replaces the one still waiting for its grid
+    if let Some(switch) = self.song_switch.take() {
+      self.scheduler.cancel(switch.id);
+    }
+    let messages = match (&song.file, self.renoise_socket) {
+      (Some(file), Some(_)) => vec![(Duration::ZERO, MessageToRenoise::LoadSong(file.clone()))],
+      _ => Vec::new(),
+    };
+    let loads = !messages.is_empty();
+    let id = self
+      .scheduler
+      .schedule_tracked(transition.quantization, messages);
+
+    // renoise schedules patterns at the end of the current one by itself, the others launch on the grid
+    let mut pattern = transition.pattern;
+    if transition.quantization == Quantization::Pattern
+      && !loads
+      && let Some(y) = pattern.take()
+    {
+      self.launch_pattern(&song_id, y, Quantization::Off, false)?;
+    }
+
+    info!("switching to song {}", song_id);
+    self.song_switch = Some(SongSwitch {
+      song_id,
+      position,
+      previous: self.setlist_position,
+      id,
+      pattern,
+      loads,
+      landed: false,
+    });
+    // without renoise, there's no grid to wait for
+    if self.renoise_socket.is_none() {
+      self.scheduler.cancel(id);
+      self.land_song_switch(Instant::now());
     }
+
+    Ok(())
+  }
+
+  /// makes the queued song the current one, once its transition's messages went out
+  ///
+  /// songs with a file get their bpm once renoise loaded it, see [`Self::song_loaded`]
+  pub fn land_song_switch(&mut self, now: Instant) {
+    let Some(switch) = self
+      .song_switch
+      .as_mut()
+      .filter(|s| !s.landed && !self.scheduler.is_queued(s.id))
+    else {
+      return;
+    };
+    let song_id = switch.song_id.clone();
+    let position = switch.position;
+    let pattern = switch.pattern.take();
+    switch.landed = true;
+    if !switch.loads {
+      self.song_switch = None;
+    }
+    if !self.set.songs.contains_key(&song_id) {
+      warn!("song {} isn't in the set anymore", song_id);
+      self.song_switch = None;
+      return;
+    }
+
+    self.current_song = Some(song_id.clone());
+    self.setlist_position = Some(position);
+    self.pattern = self.pattern.take().filter(|p| p.song_id == song_id);
+    self.recalled_scenes.clear();
+    info!("switched to song {}", song_id);
+
+    if let Some(y) = pattern
+      && let Err(e) = self.launch_pattern(&song_id, y, Quantization::Off, true)
+    {
+      warn!("couldn't launch pattern {} of song {}: {}", y, song_id, e);
+    }
+    if self.song_switch.is_none() {
+      self.sync_song_bpm(&song_id, now);
+    }
+  }
+
+  /// renoise said hello again, after loading the file of the song switched to
+  pub fn song_loaded(&mut self, now: Instant) {
+    if let Some(switch) = self.song_switch.take_if(|s| s.landed) {
+      info!("renoise loaded song {}", switch.song_id);
+      self.sync_song_bpm(&switch.song_id, now);
+    }
+  }
+
+  /// sets the bpm to a song's, if its transition syncs it
+  fn sync_song_bpm(&mut self, song_id: &str, now: Instant) {
+    let Some(song) = self.set.songs.get(song_id) else {
+      return;
+    };
+    if !song.transition.sync_bpm {
+      return;
+    }
+    match song.transition.bpm_ramp {
+      0 => self.tempo.set(song.bpm),
+      ms => self.tempo.ramp(song.bpm, Duration::from_millis(ms), now),
+    }
+  }
+
+  /// the song switch still waiting for its grid, if there's one
+  pub fn queued_song(&self) -> Option<&SongSwitch> {
+    self
+      .song_switch
+      .as_ref()
+      .filter(|s| !s.landed && self.scheduler.is_queued(s.id))
+  }
+
+  /// pattern of a song renoise is playing, the launched one first, then the one before it
+  pub fn playing_pattern(&self, song_id: &str) -> Option<i64> {
+    let transport = self.transport.as_ref().filter(|t| t.playing)?;
+    let patterns = &self.set.songs.get(song_id)?.patterns;
+    let playback = self.pattern.as_ref().filter(|p| p.song_id == song_id);
+    let playing = |y: &i64| {
+      patterns
+        .get(y)
+        .is_some_and(|p| p.contains(transport.sequence))
+    };
+
+    playback
+      .map(|p| p.y)
+      .into_iter()
+      .chain(playback.and_then(|p| p.previous))
+      .find(playing)
+      .or_else(|| patterns.keys().copied().filter(playing).min())
+  }
+
+  /// pattern of a song launched, which renoise isn't playing yet
+  pub fn queued_pattern(&self, song_id: &str) -> Option<i64> {
+    let playback = self.pattern.as_ref().filter(|p| p.song_id == song_id)?;
+    (self.playing_pattern(song_id) != Some(playback.y)).then_some(playback.y)
+  }
+
+  /// switches to the song some steps away in the setlist, staying on the current one at its ends
+  pub fn step_song(&mut self, step: i64) -> Result<()> {
+    let len = self.set.get_setlist().len() as i64;
+    // steps go on from a song still waiting for its grid
+    let from = match self.queued_song() {
+      Some(switch) => Some(switch.position),
+      None => self.setlist_position,
+    };
+    let position = match from {
+      Some(p) => p as i64 + step,
+      None => 0,
+    };
+    if position < 0 || position >= len {
+      info!("reached the end of the setlist");
+      return Ok(());
+    }
+    self.switch_song(position as usize)
+  }
+
+  /// writes the set back to its file, pretty printed like the editor does
+  ///
+  /// writes through a temporary file, so a crash while writing keeps the previous set
+  pub fn save_set(&mut self) -> Result<()> {
+    let set_file = self
+      .set_file
+      .as_ref()
+      .ok_or(anyhow::Error::msg("set has no file to be saved in"))?;
+    let temporary = format!("{}.tmp", set_file);
+    write(
+      &temporary,
+      ron::ser::to_string_pretty(&self.set, ron::ser::PrettyConfig::default())?,
+    )?;
+    rename(&temporary, set_file)?;
+    // tin's own writes don't need reloading
+    self.set_modified = metadata(set_file).and_then(|m| m.modified()).ok();
+    Ok(())
+  }
+
+  /// stores the states of the current song's buttons in a scene, then saves the set
+  ///
+  /// saving on the scene right after the last one adds it, scenes further away are refused
+  pub fn save_scene(&mut self, scene: usize) -> Result<()> {
+    let song_id = self
+      .current_song
+      .clone()
+      .ok_or(anyhow::Error::msg("no current song"))?;
+    let song = self
+      .set
+      .songs
+      .get_mut(&song_id)
+      .ok_or(anyhow::Error::msg("couldn't find song id in songs"))?;
+    if scene > song.scenes.len() {
+      return Err(anyhow::Error::msg(format!(
+        "scene {} would leave a gap, song {} has {} scene(s)",
+        scene,
+        song_id,
+        song.scenes.len()
+      )));
+    }
+
+    // stateless buttons fire on every recall otherwise
+    let states = song
+      .buttons
+      .keys()
+      .filter_map(|(x, y)| {
+        self
+          .button_states
+          .get(&(song_id.clone(), *x, *y))
+          .filter(|v| **v != SongButtonActionValue::None)
+          .map(|v| ((*x, *y), *v))
+      })
+      .collect();
+
+    match song.scenes.get_mut(scene) {
+      Some(existing) => existing.states = states,
+      None => song.scenes.push(Scene {
+        name: format!("scene {}", song.scenes.len() + 1),
+        states,
+      }),
+    }
+    info!("saved scene {} of song {}", scene, song_id);
+
+    self.save_set()
+  }
+
+  /// moves the current song's buttons to the states of a scene, sending their messages right away
+  pub fn recall_scene(&mut self, scene: usize) -> Result<()> {
+    let song_id = self
+      .current_song
+      .clone()
+      .ok_or(anyhow::Error::msg("no current song"))?;
+    let song = self
+      .set
+      .songs
+      .get(&song_id)
+      .ok_or(anyhow::Error::msg("couldn't find song id in songs"))?;
+    let states = &song
+      .scenes
+      .get(scene)
+      .ok_or(anyhow::Error::msg("couldn't find scene in song"))?
+      .states;
+    let ctx = ActionContext {
+      layout: &self.set.layout,
+      devices: self.devices_reported.then_some(&self.devices),
+    };
+
+    for ((x, y), value) in states {
+      let Some(button) = song.buttons.get(&(*x, *y)) else {
+        continue;
+      };
+      if *value == SongButtonActionValue::None {
+        continue;
+      }
+      match button.action.create_timed_renoise_message(*value, &ctx) {
+        Ok(messages) => self.scheduler.schedule_timed(Quantization::Off, messages),
+        Err(e) => warn!("couldn't recall button {},{}: {}", x, y, e),
+      }
+      self.button_states.insert((song_id.clone(), *x, *y), *value);
+    }
+
+    self.recalled_scenes.retain(|s| *s != scene);
+    self.recalled_scenes.push(scene);
+    if self.recalled_scenes.len() > 2 {
+      self.recalled_scenes.remove(0);
+    }
+    info!("recalled scene {} of song {}", scene, song_id);
+
+    Ok(())
+  }
+
+  /// sends the continuous values between the last two recalled scenes
+  ///
+  /// amount goes from 0 (the scene recalled before) to 1 (the latest one), button states are untouched
+  pub fn morph_scenes(&mut self, amount: f64) -> Result<()> {
+    let (Some(song_id), [from, to]) = (&self.current_song, self.recalled_scenes.as_slice()) else {
+      return Ok(());
+    };
+    let song = self
+      .set
+      .songs
+      .get(song_id)
+      .ok_or(anyhow::Error::msg("couldn't find song id in songs"))?;
+    let (Some(from), Some(to)) = (song.scenes.get(*from), song.scenes.get(*to)) else {
+      return Ok(());
+    };
+    let ctx = ActionContext {
+      layout: &self.set.layout,
+      devices: self.devices_reported.then_some(&self.devices),
+    };
+
+    for (pos, a) in &from.states {
+      if let Some(b) = to.states.get(pos)
+        && let Some(button) = song.buttons.get(pos)
+      {
+        match button.action.morph_renoise_message(*a, *b, amount, &ctx) {
+          Ok(messages) => self.scheduler.schedule(Quantization::Off, messages),
+          Err(e) => warn!("couldn't morph button {},{}: {}", pos.0, pos.1, e),
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// sends the bpm and the states of the current song's buttons, for a renoise that just connected
+  ///
+  /// buttons that can't be sent are skipped, so one bad reference doesn't hold the others back
+  pub fn resend_state(&mut self) -> Result<()> {
+    let Some(song_id) = self.current_song.clone() else {
+      return Ok(());
+    };
+    let song = self
+      .set
+      .songs
+      .get(&song_id)
+      .ok_or(anyhow::Error::msg("couldn't find song id in songs"))?;
+    let ctx = ActionContext {
+      layout: &self.set.layout,
+      devices: self.devices_reported.then_some(&self.devices),
+    };
+
+    self.tempo.resend();
+    for ((x, y), button) in &song.buttons {
+      let Some(value) = self.button_states.get(&(song_id.clone(), *x, *y)) else {
+        continue;
+      };
+      // stateless buttons, and the stateless steps of macros, would fire
+      match button.action.create_restore_renoise_message(*value, &ctx) {
+        Ok(messages) => self.scheduler.schedule_timed(Quantization::Off, messages),
+        Err(e) => warn!("couldn't resend button {},{}: {}", x, y, e),
+      }
+    }
+    info!("resent the state of song {}", song_id);
+
+    Ok(())
+  }
+
+  /// swaps the set for a newer version of it
+  ///
+  /// buttons with the same action keep their state, the others start from their default
+  pub fn reload_set(&mut self, set: Set, scripts: Scripts) {
+    let mut button_states = default_button_states(&set);
+    for ((song_id, x, y), state) in button_states.iter_mut() {
+      let old_button = self
+        .set
+        .songs
+        .get(song_id)
+        .and_then(|s| s.buttons.get(&(*x, *y)));
+      let new_button = set
+        .songs
+        .get(song_id)
+        .and_then(|s| s.buttons.get(&(*x, *y)));
+      if let (Some(old_button), Some(new_button)) = (old_button, new_button)
+        && old_button.action == new_button.action
+        && let Some(old_state) = self.button_states.get(&(song_id.clone(), *x, *y))
+      {
+        *state = *old_state;
+      }
+    }
+    self.button_states = button_states;
+
+    match &self.current_song {
+      Some(song_id) if !set.songs.contains_key(song_id) => {
+        warn!("current song {} isn't in the set anymore", song_id);
+        self.current_song = None;
+        self.setlist_position = None;
+        self.recalled_scenes.clear();
+      }
+      Some(song_id) => {
+        let setlist = set.get_setlist();
+        if self.setlist_position.and_then(|p| setlist.get(p)) != Some(song_id) {
+          self.setlist_position = setlist.iter().position(|s| s == song_id);
+        }
+      }
+      None => {}
+    }
+    if let Some(playback) = &self.pattern
+      && set
+        .songs
+        .get(&playback.song_id)
+        .and_then(|s| s.patterns.get(&playback.y))
+        .is_none()
+    {
+      self.pattern = None;
+    }
+
+    self.set = set;
+    self.scripts.reload(scripts);
   }
 }
@@ -0,0 +1,255 @@
+//! inputs played on virtual devices, for rehearsing and testing views without hardware
+//!
+//! a rehearsal is a script with one step per line, `#` starts a comment:
+//! ```text
+//! wait 500                  # milliseconds
+//! lpm3 press ssm            # held until released
+//! lpm3 tap grid 1 8         # pressed and released
+//! lpm3 release ssm
+//! lcxl2 move knob 8 1 127   # analog controls go from 0 to 127
+//! expect lpm3 grid 1 1 lit  # or off, as drawn on the last frame
+//! quit
+//! ```
+
+use anyhow::Result;
+use std::{
+  collections::VecDeque,
+  fs::read_to_string,
+  sync::mpsc,
+  time::{Duration, Instant},
+};
+use tin_drivers_midi::{
+  MidiVisual,
+  devices::{
+    launch_control_xl_mk2::{LCXL2Driver, LCXL2InputMessage, LCXL2Position},
+    launchpad_mini_mk3::{LPM3Driver, LPM3InputMessage, LPM3Position},
+  },
+};
+
+#[derive(Debug, Clone)]
+enum Position {
+  LPM3(LPM3Position),
+  LCXL2(LCXL2Position),
+}
+
+enum Step {
+  Wait(Duration),
+  Press(Position),
+  Release(Position),
+  Tap(Position),
+  Move(LCXL2Position, u8),
+  Expect(Position, bool),
+  Quit,
+}
+
+/// inputs of the virtual devices
+pub struct VirtualInputs {
+  pub lpm3: mpsc::Sender<LPM3InputMessage>,
+  pub lcxl2: mpsc::Sender<LCXL2InputMessage>,
+}
+
+pub enum RehearsalStatus {
+  Running,
+  /// every step ran, tin keeps going
+  Done,
+  /// the rehearsal asked to stop, with how many expectations failed
+  Quit(usize),
+}
+
+pub struct Rehearsal {
+  /// steps left, with their line
+  steps: VecDeque<(usize, Step)>,
+  waiting_until: Option<Instant>,
+  failures: usize,
+}
+
+fn number<T: std::str::FromStr>(word: Option<&str>) -> Result<T> {
+  let word = word.ok_or(anyhow::Error::msg("missing number"))?;
+  word
+    .parse()
+    .map_err(|_| anyhow::Error::msg(format!("{} isn't a number", word)))
+}
+
+fn position<'a>(device: &str, words: &mut impl Iterator<Item = &'a str>) -> Result<Position> {
+  let name = words.next().ok_or(anyhow::Error::msg("missing position"))?;
+  Ok(match (device, name) {
+    ("lpm3", "grid") => Position::LPM3(LPM3Position::Grid(
+      number(words.next())?,
+      number(words.next())?,
+    )),
+    ("lpm3", "up") => Position::LPM3(LPM3Position::Up),
+    ("lpm3", "down") => Position::LPM3(LPM3Position::Down),
+    ("lpm3", "left") => Position::LPM3(LPM3Position::Left),
+    ("lpm3", "right") => Position::LPM3(LPM3Position::Right),
+    ("lpm3", "session") => Position::LPM3(LPM3Position::Session),
+    ("lpm3", "drums") => Position::LPM3(LPM3Position::Drums),
+    ("lpm3", "keys") => Position::LPM3(LPM3Position::Keys),
+    ("lpm3", "user") => Position::LPM3(LPM3Position::User),
+    ("lpm3", "logo") => Position::LPM3(LPM3Position::Logo),
+    ("lpm3", "ssm") => Position::LPM3(LPM3Position::SSM),
+    ("lcxl2", "knob") => Position::LCXL2(LCXL2Position::Knob(
+      number(words.next())?,
+      number(words.next())?,
+    )),
+    ("lcxl2", "slider") => Position::LCXL2(LCXL2Position::Slider(number(words.next())?)),
+    ("lcxl2", "bottom") => Position::LCXL2(LCXL2Position::Bottom(
+      number(words.next())?,
+      number(words.next())?,
+    )),
+    ("lcxl2", "up") => Position::LCXL2(LCXL2Position::Up),
+    ("lcxl2", "down") => Position::LCXL2(LCXL2Position::Down),
+    ("lcxl2", "left") => Position::LCXL2(LCXL2Position::Left),
+    ("lcxl2", "right") => Position::LCXL2(LCXL2Position::Right),
+    ("lcxl2", "device") => Position::LCXL2(LCXL2Position::Device),
+    ("lcxl2", "mute") => Position::LCXL2(LCXL2Position::Mute),
+    ("lcxl2", "solo") => Position::LCXL2(LCXL2Position::Solo),
+    ("lcxl2", "record") => Position::LCXL2(LCXL2Position::Record),
+    ("lpm3" | "lcxl2", _) => {
+      return Err(anyhow::Error::msg(format!(
+        "{} has no {} position",
+        device, name
+      )));
+    }
+    _ => return Err(anyhow::Error::msg(format!("unknown device {}", device))),
+  })
+}
+
+fn parse_step(line: &str) -> Result<Option<Step>> {
+  let line = line.split('#').next().unwrap_or_default();
+  let mut words = line.split_whitespace();
+  let Some(first) = words.next() else {
+    return Ok(None);
+  };
+
+  let step = match first {
+    "wait" => Step::Wait(Duration::from_millis(number(words.next())?)),
+    "quit" => Step::Quit,
+    "expect" => {
+      let device = words.next().unwrap_or_default();
+      let position = position(device, &mut words)?;
+      match words.next() {
+        Some("lit") => Step::Expect(position, true),
+        Some("off") => Step::Expect(position, false),
+        _ => return Err(anyhow::Error::msg("expected lit or off")),
+      }
+    }
+    device => {
+      let action = words.next().unwrap_or_default();
+      match action {
+        "press" => Step::Press(position(device, &mut words)?),
+        "release" => Step::Release(position(device, &mut words)?),
+        "tap" => Step::Tap(position(device, &mut words)?),
+        "move" => match position(device, &mut words)? {
+          Position::LCXL2(p) => Step::Move(p, number(words.next())?),
+          Position::LPM3(_) => {
+            return Err(anyhow::Error::msg("only lcxl2 has analog controls"));
+          }
+        },
+        _ => return Err(anyhow::Error::msg(format!("unknown action {}", action))),
+      }
+    }
+  };
+
+  match words.next() {
+    Some(word) => Err(anyhow::Error::msg(format!("unexpected {}", word))),
+    None => Ok(Some(step)),
+  }
+}
+
+impl Rehearsal {
+  pub fn load(path: &str) -> Result<Self> {
+    let content = read_to_string(path)?;
+    let mut steps = VecDeque::new();
+    for (n, line) in content.lines().enumerate() {
+      let step = parse_step(line)
+        .map_err(|e| anyhow::Error::msg(format!("{}, line {}: {}", path, n + 1, e)))?;
+      if let Some(step) = step {
+        steps.push_back((n + 1, step));
+      }
+    }
+    Ok(Self {
+      steps,
+      waiting_until: None,
+      failures: 0,
+    })
+  }
+
+  /// plays the steps until a wait, expectations check the lights pushed on the last frame
+  pub fn update(
+    &mut self,
+    now: Instant,
+    inputs: &VirtualInputs,
+    lpm3: Option<&LPM3Driver>,
+    lcxl2: Option<&LCXL2Driver>,
+  ) -> RehearsalStatus {
+    if let Some(until) = self.waiting_until {
+      if now < until {
+        return RehearsalStatus::Running;
+      }
+      self.waiting_until = None;
+    }
+
+    while let Some((line, step)) = self.steps.pop_front() {
+      match step {
+        Step::Wait(duration) => {
+          self.waiting_until = Some(now + duration);
+          return RehearsalStatus::Running;
+        }
+        Step::Press(position) => press(inputs, position, true),
+        Step::Release(position) => press(inputs, position, false),
+        Step::Tap(position) => {
+          press(inputs, position.clone(), true);
+          press(inputs, position, false);
+        }
+        Step::Move(position, value) => {
+          let _ = inputs
+            .lcxl2
+            .send(LCXL2InputMessage::Analog(position, value.min(127)));
+        }
+        Step::Expect(position, lit) => {
+          let shown = match &position {
+            Position::LPM3(p) => lpm3
+              .and_then(|d| d.get_visual(p.clone()))
+              .is_some_and(|v| !v.is_default()),
+            Position::LCXL2(p) => lcxl2
+              .and_then(|d| d.get_visual(p.clone()))
+              .is_some_and(|v| !v.is_default()),
+          };
+          if shown != lit {
+            self.failures += 1;
+            error!(
+              "rehearsal line {}: expected {:?} to be {}",
+              line,
+              position,
+              match lit {
+                true => "lit",
+                false => "off",
+              }
+            );
+          }
+        }
+        Step::Quit => return RehearsalStatus::Quit(self.failures),
+      }
+    }
+
+    RehearsalStatus::Done
+  }
+}
+
+/// devices that went away drop their inputs
+fn press(inputs: &VirtualInputs, position: Position, pressed: bool) {
+  match position {
+    Position::LPM3(p) => {
+      let _ = inputs.lpm3.send(match pressed {
+        true => LPM3InputMessage::KeyPressed(p),
+        false => LPM3InputMessage::KeyReleased(p),
+      });
+    }
+    Position::LCXL2(p) => {
+      let _ = inputs.lcxl2.send(match pressed {
+        true => LCXL2InputMessage::KeyPressed(p),
+        false => LCXL2InputMessage::KeyReleased(p),
+      });
+    }
+  }
+}
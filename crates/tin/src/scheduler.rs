@@ -0,0 +1,255 @@
+use sophixer_core::{
+  data::quantization::Quantization,
+  messages::renoise::{MessageToRenoise, Transport},
+};
+use std::time::{Duration, Instant};
+
+struct ScheduledMessages {
+  id: u64,
+  quantization: Quantization,
+  messages: Vec<(Duration, MessageToRenoise)>,
+}
+
+/// holds messages to renoise back until the transport reaches their grid
+///
+/// delays start counting once the grid is reached
+pub struct Scheduler {
+  queue: Vec<ScheduledMessages>,
+  next_id: u64,
+  delayed: Vec<(Instant, MessageToRenoise)>,
+  last_transport: Option<Transport>,
+  /// beat and beats per bar of the link session, while it has other peers
+  session: Option<(f64, f64)>,
+  last_session_beat: Option<f64>,
+}
+
+/// messages which get renoise playing, held back for the session's next bar when it's stopped
+fn starts_transport(message: &MessageToRenoise) -> bool {
+  matches!(
+    message,
+    MessageToRenoise::PlaySection(_, _) | MessageToRenoise::StartTransport
+  )
+}
+
+impl Scheduler {
+  pub fn new() -> Self {
+    Self {
+      queue: Vec::new(),
+      next_id: 0,
+      delayed: Vec::new(),
+      last_transport: None,
+      session: None,
+      last_session_beat: None,
+    }
+  }
+
+  pub fn schedule(&mut self, quantization: Quantization, messages: Vec<MessageToRenoise>) {
+    self.schedule_timed(
+      quantization,
+      messages.into_iter().map(|m| (Duration::ZERO, m)).collect(),
+    );
+  }
+
+  pub fn schedule_timed(
+    &mut self,
+    quantization: Quantization,
+    messages: Vec<(Duration, MessageToRenoise)>,
+  ) {
+    self.schedule_tracked(quantization, messages);
+  }
+
+  /// schedules messages, returns an id to check whether they're still waiting for their grid
+  pub fn schedule_tracked(
+    &mut self,
+    quantization: Quantization,
+    messages: Vec<(Duration, MessageToRenoise)>,
+  ) -> u64 {
+    let id = self.next_id;
+    self.next_id += 1;
+    self.queue.push(ScheduledMessages {
+      id,
+      quantization,
+      messages,
+    });
+    id
+  }
+
+  /// whether scheduled messages are still waiting for their grid
+  pub fn is_queued(&self, id: u64) -> bool {
+    self.queue.iter().any(|s| s.id == id)
+  }
+
+  /// drops scheduled messages still waiting for their grid
+  pub fn cancel(&mut self, id: u64) {
+    self.queue.retain(|s| s.id != id);
+  }
+
+  pub fn clear(&mut self) {
+    self.queue.clear();
+    self.delayed.clear();
+    self.last_transport = None;
+  }
+
+  /// lines renoise's start up with a link session, see [`crate::link::LinkSession`]
+  pub fn follow_session(&mut self, session: Option<(f64, f64)>) {
+    if session.is_none() {
+      self.last_session_beat = None;
+    }
+    self.session = session;
+  }
+
+  /// returns every message due at the given transport, in scheduling order
+  ///
+  /// if renoise isn't playing, there is no grid to wait for: everything is due,
+  /// but what would start it waits for the next bar of the link session if there is one
+  pub fn poll(&mut self, transport: Option<&Transport>) -> Vec<MessageToRenoise> {
+    let now = Instant::now();
+    let mut due = Vec::new();
+    let mut kept = Vec::new();
+
+    let session_bar = match (self.session, self.last_session_beat) {
+      (Some((beat, quantum)), Some(previous)) => {
+        (previous / quantum).floor() != (beat / quantum).floor()
+      }
+      (Some(_), None) => false,
+      (None, _) => true,
+    };
+
+    for scheduled in self.queue.drain(..) {
+      let stopped_due = session_bar || !scheduled.messages.iter().any(|(_, m)| starts_transport(m));
+      let is_due = match (transport, &self.last_transport) {
+        (Some(current), _) if !current.playing => stopped_due,
+        (None, _) => stopped_due,
+        (Some(current), Some(previous)) => scheduled.quantization.is_crossed(previous, current),
+        (Some(_), None) => scheduled.quantization == Quantization::Off,
+      };
+
+      if is_due {
+        for (delay, m) in scheduled.messages {
+          if delay.is_zero() {
+            due.push(m);
+          } else {
+            self.delayed.push((now + delay, m));
+          }
+        }
+      } else {
+        kept.push(scheduled);
+      }
+    }
+
+    self.queue = kept;
+    self.last_transport = transport.cloned();
+    self.last_session_beat = self.session.map(|(beat, _)| beat);
+
+    // stable sort, so messages with the same deadline keep their order
+    self.delayed.sort_by_key(|(deadline, _)| *deadline);
+    let ready = self
+      .delayed
+      .partition_point(|(deadline, _)| *deadline <= now);
+    due.extend(self.delayed.drain(..ready).map(|(_, m)| m));
+
+    due
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn at(sequence: u64, line: u64, beats: f64) -> Transport {
+    Transport {
+      playing: true,
+      sequence,
+      line,
+      beats,
+      beats_per_bar: 4,
+    }
+  }
+
+  fn stopped() -> Transport {
+    Transport {
+      playing: false,
+      ..at(0, 0, 0.)
+    }
+  }
+
+  /// schedules a stop on a grid, after a first report of the transport
+  fn scheduled(quantization: Quantization, first: &Transport) -> Scheduler {
+    let mut scheduler = Scheduler::new();
+    scheduler.poll(Some(first));
+    scheduler.schedule(quantization, vec![MessageToRenoise::StopTransport]);
+    scheduler
+  }
+
+  #[test]
+  fn off_is_due_right_away() {
+    let mut scheduler = Scheduler::new();
+    scheduler.schedule(Quantization::Off, vec![MessageToRenoise::StopTransport]);
+    assert_eq!(scheduler.poll(Some(&at(0, 1, 0.25))).len(), 1);
+    assert!(scheduler.poll(Some(&at(0, 2, 0.5))).is_empty());
+  }
+
+  #[test]
+  fn waits_for_a_first_report() {
+    let mut scheduler = Scheduler::new();
+    scheduler.schedule(Quantization::Beat, vec![MessageToRenoise::StopTransport]);
+    assert!(scheduler.poll(Some(&at(0, 0, 0.))).is_empty());
+    assert_eq!(scheduler.poll(Some(&at(0, 4, 1.))).len(), 1);
+  }
+
+  #[test]
+  fn beat() {
+    let mut scheduler = scheduled(Quantization::Beat, &at(0, 1, 0.25));
+    assert!(scheduler.poll(Some(&at(0, 3, 0.75))).is_empty());
+    assert_eq!(scheduler.poll(Some(&at(0, 4, 1.))).len(), 1);
+  }
+
+  #[test]
+  fn bar() {
+    let mut scheduler = scheduled(Quantization::Bar, &at(0, 1, 0.25));
+    assert!(scheduler.poll(Some(&at(0, 4, 1.))).is_empty());
+    assert!(scheduler.poll(Some(&at(0, 15, 3.75))).is_empty());
+    assert_eq!(scheduler.poll(Some(&at(0, 16, 4.))).len(), 1);
+  }
+
+  #[test]
+  fn pattern() {
+    let mut scheduler = scheduled(Quantization::Pattern, &at(0, 60, 15.));
+    assert!(scheduler.poll(Some(&at(0, 63, 15.75))).is_empty());
+    assert_eq!(scheduler.poll(Some(&at(1, 0, 16.))).len(), 1);
+  }
+
+  #[test]
+  fn loop_wrapping_around() {
+    // a single pattern loop goes back to its first line, in the same sequence position
+    let mut scheduler = scheduled(Quantization::Pattern, &at(2, 63, 47.75));
+    assert_eq!(scheduler.poll(Some(&at(2, 0, 32.))).len(), 1);
+
+    // beats and bars jump back along with it
+    let mut scheduler = scheduled(Quantization::Bar, &at(2, 62, 47.5));
+    assert_eq!(scheduler.poll(Some(&at(2, 1, 32.25))).len(), 1);
+  }
+
+  #[test]
+  fn stopped_transport_has_no_grid() {
+    let mut scheduler = scheduled(Quantization::Bar, &stopped());
+    assert_eq!(scheduler.poll(Some(&stopped())).len(), 1);
+
+    let mut scheduler = Scheduler::new();
+    scheduler.schedule(Quantization::Pattern, vec![MessageToRenoise::StopTransport]);
+    assert_eq!(scheduler.poll(None).len(), 1);
+  }
+
+  #[test]
+  fn stopped_transport_waits_for_the_session() {
+    let mut scheduler = scheduled(Quantization::Off, &stopped());
+    scheduler.follow_session(Some((1.5, 4.)));
+    scheduler.schedule(Quantization::Off, vec![MessageToRenoise::StartTransport]);
+    scheduler.schedule(Quantization::Off, vec![MessageToRenoise::StopTransport]);
+    assert_eq!(scheduler.poll(Some(&stopped())).len(), 2);
+    scheduler.follow_session(Some((3.5, 4.)));
+    assert!(scheduler.poll(Some(&stopped())).is_empty());
+    scheduler.follow_session(Some((4.25, 4.)));
+    assert_eq!(scheduler.poll(Some(&stopped())).len(), 1);
+  }
+}
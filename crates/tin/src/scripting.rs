@@ -0,0 +1,405 @@
+//! buttons running rhai scripts, see [`sophixer_core::data::buttons::script::Script`]
+//!
+//! a script defines `fn press()`, called with `this` bound to a map it keeps between presses.
+//! scripts read tin's state and queue messages, timers and lights through the functions below,
+//! they can't reach files or the network, and how long they run is bounded
+
+use anyhow::Result;
+use rhai::{
+  AST, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope,
+  module_resolvers::DummyModuleResolver,
+};
+use sophixer_core::{
+  data::{
+    Set,
+    buttons::{SongButtonAction, SongButtonActionValue},
+    channels::Channel,
+    layout::TrackLayout,
+  },
+  messages::renoise::MessageToRenoise,
+};
+use std::{
+  cell::RefCell,
+  collections::HashMap,
+  fs::{metadata, read_to_string},
+  path::{Path, PathBuf},
+  rc::Rc,
+  time::{Duration, Instant, SystemTime},
+};
+
+/// operations a single call can run before it's stopped, a few milliseconds at most
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_SIZE: usize = 10_000;
+
+/// what a script sees of tin while it runs
+#[derive(Default, Clone)]
+pub struct ScriptState {
+  pub bpm: f64,
+  pub song: String,
+  pub pattern: Option<i64>,
+  pub playing: bool,
+  pub bar: Option<i64>,
+  pub beat: Option<i64>,
+  /// states of the buttons of the script's song
+  pub buttons: HashMap<(i64, i64), SongButtonActionValue>,
+}
+
+#[derive(Default)]
+struct Context {
+  layout: TrackLayout,
+  state: ScriptState,
+  /// script running, for its logs
+  name: String,
+
+  messages: Vec<MessageToRenoise>,
+  timers: Vec<(Duration, String)>,
+  /// none to leave it, some none to give the button its action's color back
+  light: Option<Option<[u8; 3]>>,
+}
+
+type Shared = Rc<RefCell<Context>>;
+
+struct LoadedScript {
+  file: String,
+  source: String,
+  modified: Option<SystemTime>,
+  ast: AST,
+  /// bound to `this`
+  memory: Dynamic,
+  timers: Vec<(Instant, String)>,
+  light: Option<[u8; 3]>,
+}
+
+/// scripts of the set's buttons, by song and position
+pub struct Scripts {
+  engine: Engine,
+  context: Shared,
+  scripts: HashMap<(String, i64, i64), LoadedScript>,
+}
+
+type RhaiResult<T> = Result<T, Box<EvalAltResult>>;
+
+fn index(n: i64) -> RhaiResult<u64> {
+  u64::try_from(n).map_err(|_| format!("{} isn't a valid index", n).into())
+}
+
+fn option(value: Option<i64>) -> Dynamic {
+  value.map(Dynamic::from).unwrap_or(Dynamic::UNIT)
+}
+
+fn send(context: &Shared, message: MessageToRenoise) {
+  context.borrow_mut().messages.push(message);
+}
+
+fn create_engine(context: &Shared) -> Engine {
+  let mut engine = Engine::new();
+
+  engine
+    .set_max_operations(MAX_OPERATIONS)
+    .set_max_call_levels(MAX_CALL_LEVELS)
+    .set_max_string_size(MAX_SIZE)
+    .set_max_array_size(MAX_SIZE)
+    .set_max_map_size(MAX_SIZE)
+    .set_module_resolver(DummyModuleResolver::new())
+    .disable_symbol("eval");
+
+  let c = context.clone();
+  engine.on_print(move |s| info!("script {}: {}", c.borrow().name, s));
+  let c = context.clone();
+  engine.on_debug(move |s, _, _| debug!("script {}: {}", c.borrow().name, s));
+
+  // state
+  let c = context.clone();
+  engine.register_fn("bpm", move || c.borrow().state.bpm);
+  let c = context.clone();
+  engine.register_fn("song", move || c.borrow().state.song.clone());
+  let c = context.clone();
+  engine.register_fn("pattern", move || option(c.borrow().state.pattern));
+  let c = context.clone();
+  engine.register_fn("playing", move || c.borrow().state.playing);
+  let c = context.clone();
+  engine.register_fn("bar", move || option(c.borrow().state.bar));
+  let c = context.clone();
+  engine.register_fn("beat", move || option(c.borrow().state.beat));
+  let c = context.clone();
+  engine.register_fn("state", move |x: i64, y: i64| {
+    match c.borrow().state.buttons.get(&(x, y)) {
+      Some(SongButtonActionValue::Boolean(b)) => Dynamic::from(*b),
+      Some(SongButtonActionValue::Number(n)) => Dynamic::from(*n as i64),
+      Some(SongButtonActionValue::None) | None => Dynamic::UNIT,
+    }
+  });
+
+  // tracks of the layout
+  let c = context.clone();
+  engine.register_fn("track", move |group: &str, n: i64| -> RhaiResult<i64> {
+    let channel = Channel::Track(group.to_string(), index(n)?);
+    c.borrow()
+      .layout
+      .resolve(&channel)
+      .map(|t| t as i64)
+      .map_err(|e| e.to_string().into())
+  });
+  let c = context.clone();
+  engine.register_fn("master", move || -> RhaiResult<i64> {
+    c.borrow()
+      .layout
+      .resolve(&Channel::Master)
+      .map(|t| t as i64)
+      .map_err(|e| e.to_string().into())
+  });
+
+  // messages
+  let c = context.clone();
+  engine.register_fn(
+    "play_section",
+    move |start: i64, force: bool| -> RhaiResult<()> {
+      send(&c, MessageToRenoise::PlaySection(index(start)?, force));
+      Ok(())
+    },
+  );
+  let c = context.clone();
+  engine.register_fn("set_loop", move |start: i64, end: i64| -> RhaiResult<()> {
+    send(&c, MessageToRenoise::SetLoop(index(start)?, index(end)?));
+    Ok(())
+  });
+  let c = context.clone();
+  engine.register_fn("start", move || send(&c, MessageToRenoise::StartTransport));
+  let c = context.clone();
+  engine.register_fn("stop", move || send(&c, MessageToRenoise::StopTransport));
+  let c = context.clone();
+  engine.register_fn("mute", move |track: i64, muted: bool| -> RhaiResult<()> {
+    send(&c, MessageToRenoise::MuteTrack(index(track)?, muted));
+    Ok(())
+  });
+  let c = context.clone();
+  engine.register_fn(
+    "bypass",
+    move |track: i64, device: i64, bypassed: bool| -> RhaiResult<()> {
+      send(
+        &c,
+        MessageToRenoise::BypassEffect(index(track)?, index(device)?, bypassed),
+      );
+      Ok(())
+    },
+  );
+  let c = context.clone();
+  engine.register_fn(
+    "set_parameter",
+    move |track: i64, device: i64, parameter: i64, value: f64| -> RhaiResult<()> {
+      send(
+        &c,
+        MessageToRenoise::SetParameterValue(
+          index(track)?,
+          index(device)?,
+          index(parameter)?,
+          value,
+        ),
+      );
+      Ok(())
+    },
+  );
+  let c = context.clone();
+  engine.register_fn("set_bpm", move |bpm: f64| {
+    send(&c, MessageToRenoise::SetBPM(bpm))
+  });
+  let c = context.clone();
+  engine.register_fn("set_volume", move |volume: f64| {
+    send(&c, MessageToRenoise::SetMasterVolume(volume))
+  });
+
+  // timers and lights
+  let c = context.clone();
+  engine.register_fn("after", move |ms: i64, function: &str| -> RhaiResult<()> {
+    let delay = Duration::from_millis(index(ms)?);
+    c.borrow_mut().timers.push((delay, function.to_string()));
+    Ok(())
+  });
+  let c = context.clone();
+  engine.register_fn("light", move |r: i64, g: i64, b: i64| {
+    let channel = |v: i64| v.clamp(0, 255) as u8;
+    c.borrow_mut().light = Some(Some([channel(r), channel(g), channel(b)]));
+  });
+  let c = context.clone();
+  engine.register_fn("reset_light", move || {
+    c.borrow_mut().light = Some(None);
+  });
+
+  engine
+}
+
+/// script files are found next to the set file
+fn script_path(file: &str, set_file: Option<&str>) -> PathBuf {
+  match set_file.and_then(|f| Path::new(f).parent()) {
+    Some(dir) => dir.join(file),
+    None => PathBuf::from(file),
+  }
+}
+
+fn compile(engine: &Engine, file: &str, set_file: Option<&str>) -> Result<LoadedScript> {
+  let path = script_path(file, set_file);
+  let source = read_to_string(&path)
+    .map_err(|e| anyhow::Error::msg(format!("couldn't read {}: {}", path.display(), e)))?;
+  let ast = engine
+    .compile(&source)
+    .map_err(|e| anyhow::Error::msg(format!("{}: {}", file, e)))?;
+  if !ast
+    .iter_functions()
+    .any(|f| f.name == "press" && f.params.is_empty())
+  {
+    return Err(anyhow::Error::msg(format!(
+      "{} has no press() function",
+      file
+    )));
+  }
+
+  Ok(LoadedScript {
+    file: file.to_string(),
+    source,
+    modified: metadata(&path).and_then(|m| m.modified()).ok(),
+    ast,
+    memory: Dynamic::from(Map::new()),
+    timers: Vec::new(),
+    light: None,
+  })
+}
+
+impl Scripts {
+  pub fn new() -> Self {
+    let context = Shared::default();
+    Self {
+      engine: create_engine(&context),
+      context,
+      scripts: HashMap::new(),
+    }
+  }
+
+  /// compiles the scripts of a set
+  pub fn load(set: &Set, set_file: Option<&str>) -> Result<Self> {
+    let mut scripts = Self::new();
+    scripts.context.borrow_mut().layout = set.layout.clone();
+
+    for (song_id, song) in &set.songs {
+      for ((x, y), button) in &song.buttons {
+        if let SongButtonAction::Script(action) = &button.action {
+          let script = compile(&scripts.engine, &action.file, set_file).map_err(|e| {
+            anyhow::Error::msg(format!("song {}, button {},{}: {}", song_id, x, y, e))
+          })?;
+          scripts.scripts.insert((song_id.clone(), *x, *y), script);
+        }
+      }
+    }
+
+    Ok(scripts)
+  }
+
+  /// swaps in newly loaded scripts, those that didn't change keep their memory, timers and light
+  pub fn reload(&mut self, mut scripts: Scripts) {
+    for (key, script) in scripts.scripts.iter_mut() {
+      if let Some(old) = self.scripts.remove(key)
+        && old.source == script.source
+      {
+        script.memory = old.memory;
+        script.timers = old.timers;
+        script.light = old.light;
+      }
+    }
+    *self = scripts;
+  }
+
+  /// whether a script file changed since the last check, so broken scripts are only reported once
+  pub fn changed(&mut self, set_file: Option<&str>) -> bool {
+    let mut changed = false;
+    for script in self.scripts.values_mut() {
+      let modified = metadata(script_path(&script.file, set_file))
+        .and_then(|m| m.modified())
+        .ok();
+      if modified != script.modified {
+        script.modified = modified;
+        changed = true;
+      }
+    }
+    changed
+  }
+
+  /// color a script gave its button
+  pub fn light(&self, song_id: &str, pos: (i64, i64)) -> Option<[u8; 3]> {
+    self
+      .scripts
+      .get(&(song_id.to_string(), pos.0, pos.1))
+      .and_then(|s| s.light)
+  }
+
+  /// timers that are due, as the song and position of their button with the function to call
+  pub fn due(&mut self, now: Instant) -> Vec<(String, (i64, i64), String)> {
+    let mut due = Vec::new();
+    for ((song_id, x, y), script) in self.scripts.iter_mut() {
+      script.timers.sort_by_key(|(deadline, _)| *deadline);
+      let ready = script
+        .timers
+        .partition_point(|(deadline, _)| *deadline <= now);
+      due.extend(
+        script
+          .timers
+          .drain(..ready)
+          .map(|(_, function)| (song_id.clone(), (*x, *y), function)),
+      );
+    }
+    due
+  }
+
+  /// runs a function of a button's script, returns the messages it sent
+  ///
+  /// errors are logged, a failing script shouldn't stop the show
+  pub fn call(
+    &mut self,
+    song_id: &str,
+    pos: (i64, i64),
+    function: &str,
+    state: ScriptState,
+    now: Instant,
+  ) -> Vec<MessageToRenoise> {
+    let Some(script) = self.scripts.get_mut(&(song_id.to_string(), pos.0, pos.1)) else {
+      warn!(
+        "button {},{} of {} has no script loaded",
+        pos.0, pos.1, song_id
+      );
+      return Vec::new();
+    };
+
+    {
+      let mut context = self.context.borrow_mut();
+      context.state = state;
+      context.name = script.file.clone();
+    }
+    let options = CallFnOptions::new()
+      .eval_ast(false)
+      .bind_this_ptr(&mut script.memory);
+    let result = self.engine.call_fn_with_options::<Dynamic>(
+      options,
+      &mut Scope::new(),
+      &script.ast,
+      function,
+      (),
+    );
+
+    let mut context = self.context.borrow_mut();
+    let messages = std::mem::take(&mut context.messages);
+    let timers = std::mem::take(&mut context.timers);
+    let light = context.light.take();
+
+    // what a script queued before failing is dropped, its memory keeps what it changed
+    if let Err(e) = result {
+      error!("script {}, {}(): {}", script.file, function, e);
+      return Vec::new();
+    }
+    script
+      .timers
+      .extend(timers.into_iter().map(|(delay, f)| (now + delay, f)));
+    if let Some(light) = light {
+      script.light = light;
+    }
+    messages
+  }
+}
@@ -1,6 +1,10 @@
 use anyhow::Result;
 use intercom::server::{InterServerCommunicator, udp::UdpServer};
-use sophixer_core::messages::renoise::{MessageFromRenoise, MessageToRenoise};
+use sophixer_core::{
+  data::references::DeviceChains,
+  messages::renoise::{MessageFromRenoise, MessageToRenoise},
+};
+use std::time::Instant;
 
 use crate::model::TinModel;
 
@@ -18,17 +22,79 @@ impl RenoiseCommunicator {
         match msg {
           MessageFromRenoise::Hello => {
             model.renoise_socket = Some(from);
+            model.devices = DeviceChains::default();
+            model.devices_reported = false;
+            model.tempo.connect();
             info!("renoise connected");
+            model.song_loaded(Instant::now());
             RenoiseCommunicator::send_message(server, from, MessageToRenoise::Welcome)?;
           }
           MessageFromRenoise::Goodbye => {
             model.renoise_socket = None;
+            model.transport = None;
+            model.scheduler.clear();
+            model.pattern = None;
+            // nothing's left to wait for
+            model.land_song_switch(Instant::now());
             info!("renoise disconnected");
           }
+          MessageFromRenoise::Transport(transport) => {
+            if let Some(previous) = model.transport.take() {
+              // a follow that can't happen is dropped, the pattern keeps looping
+              if let Err(e) = model.follow_transport(&previous, &transport) {
+                warn!("couldn't follow the pattern: {}", e);
+              }
+            }
+            model.transport = Some(transport);
+          }
+          MessageFromRenoise::Bpm(bpm) => {
+            model.tempo.report(bpm, Instant::now());
+          }
+          MessageFromRenoise::Device(track, device, name) => {
+            model.devices.insert_device(track, device, name);
+          }
+          MessageFromRenoise::Parameter(track, device, parameter, name) => {
+            model
+              .devices
+              .insert_parameter(track, device, parameter, name);
+          }
+          MessageFromRenoise::DevicesReported => {
+            model.devices_reported = true;
+            let unresolved = model.set.check_references(&model.devices);
+            for d in &unresolved {
+              warn!("{}", d);
+            }
+            info!(
+              "renoise reported its devices, {} unresolved reference(s)",
+              unresolved.len()
+            );
+            model.resend_state()?;
+          }
         }
       }
     }
 
     Ok(())
   }
+
+  /// sends the scheduled messages which reached their grid, and the tempo's bpm if it moved
+  pub fn send_scheduled(model: &mut TinModel, server: &UdpServer) -> Result<()> {
+    let now = Instant::now();
+    // messages wait in the scheduler while renoise is away
+    if let Some(rsa) = model.renoise_socket {
+      let due = model.scheduler.poll(model.transport.as_ref());
+      for m in due {
+        if let MessageToRenoise::SetBPM(bpm) = m {
+          model.tempo.follow(bpm, now);
+        }
+        RenoiseCommunicator::send_message(server, rsa, m)?;
+      }
+      model.land_song_switch(now);
+      if let Some(bpm) = model.tempo.update(now) {
+        RenoiseCommunicator::send_message(server, rsa, MessageToRenoise::SetBPM(bpm))?;
+      }
+    }
+
+    Ok(())
+  }
 }
@@ -0,0 +1,86 @@
+//! runtime state written next to the set file, so a restarted tin can pick up where it stopped
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sophixer_core::data::buttons::SongButtonActionValue;
+use std::{
+  collections::HashMap,
+  fs::{read_to_string, rename, write},
+  time::Duration,
+};
+
+use crate::model::{LPM3View, TinModel};
+
+/// how often the state is checked for changes
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Snapshot {
+  pub current_song: Option<String>,
+  pub setlist_position: Option<usize>,
+  pub bpm: f64,
+  pub lpm3view: LPM3View,
+  pub camera: (i64, i64),
+  pub button_states: HashMap<(String, i64, i64), SongButtonActionValue>,
+  pub recalled_scenes: Vec<usize>,
+}
+
+impl Snapshot {
+  pub fn path(set_file: &str) -> String {
+    format!("{}.state", set_file)
+  }
+
+  pub fn take(tin: &TinModel) -> Self {
+    Self {
+      current_song: tin.current_song.clone(),
+      setlist_position: tin.setlist_position,
+      bpm: tin.tempo.target,
+      lpm3view: tin.lpm3view,
+      camera: tin.camera,
+      button_states: tin.button_states.clone(),
+      recalled_scenes: tin.recalled_scenes.clone(),
+    }
+  }
+
+  /// restores the state, leaving out songs and buttons the set doesn't have anymore
+  pub fn apply(self, tin: &mut TinModel) {
+    match self.current_song {
+      Some(song_id) if !tin.set.songs.contains_key(&song_id) => {
+        warn!("song {} isn't in the set anymore", song_id);
+      }
+      current_song => {
+        // the setlist may have changed since
+        let setlist = tin.set.get_setlist();
+        tin.setlist_position = match self.setlist_position {
+          Some(p) if setlist.get(p) == current_song.as_ref() => Some(p),
+          _ => setlist
+            .iter()
+            .position(|s| Some(s) == current_song.as_ref()),
+        };
+        tin.current_song = current_song;
+        tin.camera = self.camera;
+        tin.recalled_scenes = self.recalled_scenes;
+      }
+    }
+    tin.tempo.set(self.bpm);
+    tin.lpm3view = self.lpm3view;
+
+    for (key, value) in self.button_states {
+      if let Some(state) = tin.button_states.get_mut(&key) {
+        *state = value;
+      }
+    }
+  }
+
+  pub fn read(path: &str) -> Result<Self> {
+    Ok(ron::from_str(&read_to_string(path)?)?)
+  }
+
+  /// writes through a temporary file, so a crash while writing keeps the previous snapshot
+  pub fn write(&self, path: &str) -> Result<()> {
+    let temporary = format!("{}.tmp", path);
+    write(&temporary, ron::to_string(self)?)?;
+    rename(&temporary, path)?;
+    Ok(())
+  }
+}
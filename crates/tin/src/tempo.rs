@@ -0,0 +1,308 @@
+//! bpm tin wants renoise at, and how it gets there
+
+use std::time::{Duration, Instant};
+
+/// bpms renoise accepts
+const MIN_BPM: f64 = 32.;
+const MAX_BPM: f64 = 999.;
+
+/// smallest change worth sending to renoise
+const EPSILON: f64 = 0.01;
+
+/// time between two bpms sent along a ramp
+const RAMP_STEP: Duration = Duration::from_millis(50);
+
+/// a tap after this long starts counting again
+const TAP_TIMEOUT: Duration = Duration::from_secs(2);
+/// taps averaged into the tapped bpm
+const TAPS: usize = 4;
+
+/// how long renoise gets to apply a bpm before disagreeing reports are taken as its own changes
+const SETTLE: Duration = Duration::from_millis(500);
+
+struct Ramp {
+  from: f64,
+  start: Instant,
+  duration: Duration,
+}
+
+pub struct Tempo {
+  /// bpm tin wants renoise at, where the ramp ends if there is one
+  pub target: f64,
+  /// bpm renoise reported last
+  pub reported: Option<f64>,
+  /// bpm sent to renoise last, and when, none if nothing was sent since it connected
+  sent: Option<(f64, Instant)>,
+  /// the bpm goes out on the next update, whatever renoise reported
+  resend: bool,
+  ramp: Option<Ramp>,
+  taps: Vec<Instant>,
+}
+
+impl Tempo {
+  pub fn new(bpm: f64) -> Self {
+    Self {
+      target: bpm.clamp(MIN_BPM, MAX_BPM),
+      reported: None,
+      sent: None,
+      resend: false,
+      ramp: None,
+      taps: Vec::new(),
+    }
+  }
+
+  /// jumps to a bpm, stopping any ramp
+  pub fn set(&mut self, bpm: f64) {
+    self.ramp = None;
+    self.target = bpm.clamp(MIN_BPM, MAX_BPM);
+    self.resend = true;
+  }
+
+  /// moves the bpm by some amount, from where the ramp is if there is one
+  pub fn nudge(&mut self, amount: f64, now: Instant) {
+    let bpm = self.current(now) + amount;
+    self.set(bpm);
+  }
+
+  /// glides from the current bpm to another one
+  pub fn ramp(&mut self, bpm: f64, duration: Duration, now: Instant) {
+    let from = self.current(now);
+    self.set(bpm);
+    if !duration.is_zero() {
+      self.ramp = Some(Ramp {
+        from,
+        start: now,
+        duration,
+      });
+    }
+  }
+
+  /// bpm at some point of the ramp
+  pub fn current(&self, now: Instant) -> f64 {
+    match &self.ramp {
+      Some(ramp) => {
+        let amount = (now - ramp.start).as_secs_f64() / ramp.duration.as_secs_f64();
+        ramp.from + (self.target - ramp.from) * amount.min(1.)
+      }
+      None => self.target,
+    }
+  }
+
+  pub fn is_ramping(&self) -> bool {
+    self.ramp.is_some()
+  }
+
+  /// counts a tap, and sets the bpm to the taps' once there are two of them
+  pub fn tap(&mut self, now: Instant) -> Option<f64> {
+    if let Some(last) = self.taps.last()
+      && now - *last > TAP_TIMEOUT
+    {
+      self.taps.clear();
+    }
+    self.taps.push(now);
+    if self.taps.len() > TAPS + 1 {
+      self.taps.remove(0);
+    }
+
+    let (first, last) = (self.taps.first()?, self.taps.last()?);
+    let intervals = self.taps.len() - 1;
+    if intervals == 0 {
+      return None;
+    }
+    let interval = (*last - *first).as_secs_f64() / intervals as f64;
+    self.set(60. / interval);
+    Some(self.target)
+  }
+
+  /// a bpm renoise reported
+  ///
+  /// changes tin didn't ask for were made in renoise, so they become the target,
+  /// as does the bpm of a renoise that connected while tin had nothing to send it
+  pub fn report(&mut self, bpm: f64, now: Instant) {
+    let changed = self.reported.is_none_or(|r| (r - bpm).abs() > EPSILON);
+    self.reported = Some(bpm);
+    if self.resend {
+      return;
+    }
+
+    let adopt = match self.sent {
+      None => true,
+      Some((sent, at)) => {
+        changed && self.ramp.is_none() && now - at > SETTLE && (sent - bpm).abs() > EPSILON
+      }
+    };
+    if adopt {
+      info!("following renoise's bpm of {}", bpm);
+      self.ramp = None;
+      self.target = bpm;
+      self.sent = Some((bpm, now));
+    }
+  }
+
+  /// forgets about the previous renoise, for one that just connected
+  pub fn connect(&mut self) {
+    self.reported = None;
+    self.sent = None;
+    self.resend = false;
+  }
+
+  /// sends the target again, for a renoise that should pick up tin's bpm
+  pub fn resend(&mut self) {
+    self.resend = true;
+  }
+
+  /// notes a bpm that got sent to renoise without the tempo, like transitions on their grid
+  pub fn follow(&mut self, bpm: f64, now: Instant) {
+    self.set(bpm);
+    self.resend = false;
+    self.sent = Some((self.target, now));
+  }
+
+  /// moves the ramp along, returns the bpm to send to renoise if it changed
+  pub fn update(&mut self, now: Instant) -> Option<f64> {
+    let bpm = self.current(now);
+    if let Some(ramp) = &self.ramp {
+      if now - ramp.start >= ramp.duration {
+        self.ramp = None;
+      } else if let Some((_, at)) = self.sent
+        && now - at < RAMP_STEP
+      {
+        return None;
+      }
+    }
+
+    let due = match self.sent {
+      Some((sent, _)) => self.resend || (sent - bpm).abs() > EPSILON,
+      None => self.resend,
+    };
+    if !due {
+      return None;
+    }
+    self.resend = false;
+    self.sent = Some((bpm, now));
+    Some(bpm)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+  }
+
+  /// a tempo renoise is connected to, agreeing on its bpm
+  fn connected(bpm: f64, now: Instant) -> Tempo {
+    let mut tempo = Tempo::new(bpm);
+    tempo.report(bpm, now);
+    tempo
+  }
+
+  #[test]
+  fn ramp_glides_in_steps() {
+    let connect = Instant::now();
+    let mut tempo = connected(100., connect);
+    let start = connect + ms(1000);
+    tempo.ramp(120., ms(1000), start);
+    assert!(tempo.is_ramping());
+
+    assert_eq!(tempo.update(start), Some(100.));
+    // too soon after the last step
+    assert_eq!(tempo.update(start + ms(20)), None);
+    let halfway = tempo.update(start + ms(500)).unwrap();
+    assert!((halfway - 110.).abs() < 0.01);
+
+    assert_eq!(tempo.update(start + ms(1200)), Some(120.));
+    assert!(!tempo.is_ramping());
+    assert_eq!(tempo.update(start + ms(1300)), None);
+  }
+
+  #[test]
+  fn zero_ramp_jumps() {
+    let start = Instant::now();
+    let mut tempo = connected(100., start);
+    tempo.ramp(120., Duration::ZERO, start);
+    assert!(!tempo.is_ramping());
+    assert_eq!(tempo.update(start), Some(120.));
+  }
+
+  #[test]
+  fn nudge_from_the_ramp() {
+    let start = Instant::now();
+    let mut tempo = connected(100., start);
+    tempo.ramp(120., ms(1000), start);
+    tempo.nudge(1., start + ms(500));
+    assert!(!tempo.is_ramping());
+    assert!((tempo.target - 111.).abs() < 0.01);
+  }
+
+  #[test]
+  fn taps_are_averaged() {
+    let start = Instant::now();
+    let mut tempo = Tempo::new(100.);
+    assert_eq!(tempo.tap(start), None);
+    // 120 bpm, then a late tap pulling the average down
+    let bpm = tempo.tap(start + ms(500)).unwrap();
+    assert!((bpm - 120.).abs() < 0.01);
+    let bpm = tempo.tap(start + ms(1000)).unwrap();
+    assert!((bpm - 120.).abs() < 0.01);
+    let bpm = tempo.tap(start + ms(1600)).unwrap();
+    assert!((bpm - 112.5).abs() < 0.01);
+
+    // only the last taps count
+    for n in 1..=4 {
+      tempo.tap(start + ms(1600 + n * 400));
+    }
+    assert!((tempo.target - 150.).abs() < 0.01);
+  }
+
+  #[test]
+  fn taps_time_out() {
+    let start = Instant::now();
+    let mut tempo = Tempo::new(100.);
+    tempo.tap(start);
+    tempo.tap(start + ms(500));
+    assert_eq!(tempo.tap(start + ms(3000)), None);
+    let bpm = tempo.tap(start + ms(3250)).unwrap();
+    assert!((bpm - 240.).abs() < 0.01);
+  }
+
+  #[test]
+  fn renoise_bpm_is_adopted_on_connect() {
+    let start = Instant::now();
+    let mut tempo = Tempo::new(100.);
+    tempo.report(130., start);
+    assert_eq!(tempo.target, 130.);
+    assert_eq!(tempo.update(start), None);
+  }
+
+  #[test]
+  fn reports_wait_for_renoise_to_settle() {
+    let start = Instant::now();
+    let mut tempo = connected(100., start);
+    tempo.set(120.);
+    assert_eq!(tempo.update(start), Some(120.));
+
+    // renoise still at the old bpm, it hasn't applied the new one yet
+    tempo.report(100., start + ms(100));
+    assert_eq!(tempo.target, 120.);
+    tempo.report(120., start + ms(200));
+    assert_eq!(tempo.target, 120.);
+
+    // changed in renoise, once it settled
+    tempo.report(90., start + ms(1000));
+    assert_eq!(tempo.target, 90.);
+    assert_eq!(tempo.update(start + ms(1000)), None);
+  }
+
+  #[test]
+  fn resend_ignores_reports() {
+    let start = Instant::now();
+    let mut tempo = connected(100., start);
+    tempo.resend();
+    tempo.report(90., start + ms(1000));
+    assert_eq!(tempo.target, 100.);
+    assert_eq!(tempo.update(start + ms(1000)), Some(100.));
+  }
+}
@@ -0,0 +1,325 @@
+//! dashboard shown in the terminal, so tin's state can be seen at a glance from the laptop
+//!
+//! logs go to the dashboard instead of the terminal while it's shown
+
+use anyhow::Result;
+use ratatui::{
+  DefaultTerminal, Frame,
+  crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+  layout::{Constraint, Layout, Rect},
+  style::{Color, Style, Stylize},
+  text::{Line, Span},
+  widgets::{Block, Paragraph},
+};
+use std::{
+  collections::VecDeque,
+  io::Write,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+use tin_drivers_midi::{
+  devices::{
+    launch_control_xl_mk2::{LCXL2Driver, LCXL2Position},
+    launchpad_mini_mk3::{LPM3Driver, LPM3Position},
+  },
+  surface::{FaderControl, FaderSurface},
+};
+
+use crate::frames::FrameMetrics;
+use crate::model::TinModel;
+
+/// time between two draws, the loop runs a lot faster than the terminal needs
+const DRAW_INTERVAL: Duration = Duration::from_millis(100);
+/// lines of the log kept
+const LOG_LINES: usize = 500;
+
+/// lines logged, kept for the dashboard
+#[derive(Clone, Default)]
+pub struct LogBuffer(Arc<Mutex<VecDeque<String>>>);
+
+impl Write for LogBuffer {
+  fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    if let Ok(mut lines) = self.0.lock() {
+      for line in String::from_utf8_lossy(buf).lines() {
+        lines.push_back(line.to_string());
+      }
+      while lines.len() > LOG_LINES {
+        lines.pop_front();
+      }
+    }
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    Ok(())
+  }
+}
+
+pub struct Dashboard {
+  terminal: DefaultTerminal,
+  log: LogBuffer,
+  last_draw: Option<Instant>,
+}
+
+/// a color of the devices, from 0 to 127 per channel
+fn device_color((r, g, b): (u8, u8, u8)) -> Color {
+  Color::Rgb(
+    r.saturating_mul(2),
+    g.saturating_mul(2),
+    b.saturating_mul(2),
+  )
+}
+
+fn presence(connected: bool) -> Span<'static> {
+  match connected {
+    true => "connected".green(),
+    false => "missing".red(),
+  }
+}
+
+impl Dashboard {
+  pub fn start(log: LogBuffer) -> Result<Self> {
+    let terminal = ratatui::try_init()?;
+    Ok(Self {
+      terminal,
+      log,
+      last_draw: None,
+    })
+  }
+
+  /// draws the dashboard if it's time to, returns false once the operator asked to quit
+  pub fn update(
+    &mut self,
+    now: Instant,
+    tin: &TinModel,
+    metrics: &FrameMetrics,
+    lpm3: Option<&LPM3Driver>,
+    lcxl2: Option<&LCXL2Driver>,
+  ) -> Result<bool> {
+    while event::poll(Duration::ZERO)? {
+      if let Event::Key(key) = event::read()?
+        && key.kind == KeyEventKind::Press
+      {
+        match key.code {
+          KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+          KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(false),
+          _ => {}
+        }
+      }
+    }
+
+    if self.last_draw.is_some_and(|at| now - at < DRAW_INTERVAL) {
+      return Ok(true);
+    }
+    self.last_draw = Some(now);
+
+    let log = self.log.clone();
+    self.terminal.draw(|frame| {
+      let [top, bottom] =
+        Layout::vertical([Constraint::Length(11), Constraint::Min(3)]).areas(frame.area());
+      let [status, grid, faders] = Layout::horizontal([
+        Constraint::Min(30),
+        Constraint::Length(20),
+        Constraint::Length(34),
+      ])
+      .areas(top);
+
+      draw_status(
+        frame,
+        status,
+        now,
+        tin,
+        metrics,
+        lpm3.is_some(),
+        lcxl2.is_some(),
+      );
+      draw_grid(frame, grid, lpm3);
+      draw_faders(frame, faders, lcxl2);
+      draw_log(frame, bottom, &log);
+    })?;
+
+    Ok(true)
+  }
+}
+
+/// the terminal is given back however tin stops
+impl Drop for Dashboard {
+  fn drop(&mut self) {
+    ratatui::restore();
+  }
+}
+
+fn draw_status(
+  frame: &mut Frame,
+  area: Rect,
+  now: Instant,
+  tin: &TinModel,
+  metrics: &FrameMetrics,
+  lpm3: bool,
+  lcxl2: bool,
+) {
+  let mut lines = vec![
+    Line::from(vec![
+      "renoise         ".bold(),
+      match tin.renoise_socket {
+        Some(socket) => format!("connected from {}", socket).green(),
+        None => "waiting".red(),
+      },
+    ]),
+    Line::from(vec!["launchpad       ".bold(), presence(lpm3)]),
+    Line::from(vec!["launch control  ".bold(), presence(lcxl2)]),
+  ];
+
+  let song = tin
+    .current_song
+    .as_ref()
+    .and_then(|id| tin.set.songs.get(id));
+  lines.push(Line::from(vec![
+    "song            ".bold(),
+    match song {
+      Some(song) => Span::raw(match tin.setlist_position {
+        Some(p) => format!("{} ({}/{})", song.name, p + 1, tin.set.get_setlist().len()),
+        None => song.name.clone(),
+      }),
+      None => "none".dark_gray(),
+    },
+  ]));
+
+  let mut bpm = vec![
+    "bpm             ".bold(),
+    Span::raw(format!("{:.2}", tin.tempo.current(now))),
+  ];
+  if tin.tempo.is_ramping() {
+    bpm.push(format!(" ramping to {:.2}", tin.tempo.target).yellow());
+  }
+  if let Some(reported) = tin.tempo.reported {
+    bpm.push(format!(" renoise at {:.2}", reported).dark_gray());
+  }
+  lines.push(Line::from(bpm));
+
+  lines.push(Line::from(vec![
+    "pattern         ".bold(),
+    match &tin.pattern {
+      Some(pattern) => Span::raw(format!("{}, loop {}", pattern.y, pattern.loops + 1)),
+      None => "none".dark_gray(),
+    },
+  ]));
+
+  lines.push(Line::from(vec![
+    "transport       ".bold(),
+    match &tin.transport {
+      Some(t) if t.playing => format!(
+        "playing, bar {} beat {}",
+        t.bar() + 1,
+        t.beat() % t.beats_per_bar.max(1) as i64 + 1
+      )
+      .green(),
+      Some(_) => "stopped".yellow(),
+      None => "unknown".dark_gray(),
+    },
+  ]));
+
+  let mut frames = vec![
+    "loop            ".bold(),
+    Span::raw(format!(
+      "{:.0} fps, {:.1?} per frame, {:.1?} at most",
+      metrics.frames_per_second, metrics.frame_average, metrics.frame_max
+    )),
+  ];
+  if let (Some(average), Some(max)) = (metrics.latency_average, metrics.latency_max) {
+    frames.push(format!(", inputs out in {:.1?}, {:.1?} at most", average, max).dark_gray());
+  }
+  lines.push(Line::from(frames));
+
+  frame.render_widget(
+    Paragraph::new(lines).block(Block::bordered().title(" tin ")),
+    area,
+  );
+}
+
+/// the launchpad's 9x9 buttons as they're lit
+fn draw_grid(frame: &mut Frame, area: Rect, lpm3: Option<&LPM3Driver>) {
+  let mut lines = Vec::new();
+  if let Some(lpm3) = lpm3 {
+    for y in (1..=9).rev() {
+      let cells = (1..=9)
+        .map(|x| {
+          let rgb = lpm3
+            .get_visual(LPM3Position::Raw(y * 10 + x))
+            .map(|v| v.to_rgb())
+            .unwrap_or_default();
+          Span::styled("  ", Style::new().bg(device_color(rgb)))
+        })
+        .collect::<Vec<_>>();
+      lines.push(Line::from(cells));
+    }
+  }
+
+  frame.render_widget(
+    Paragraph::new(lines).block(Block::bordered().title(" launchpad ")),
+    area,
+  );
+}
+
+/// where the launch control's knobs and sliders are, colored as they're lit
+fn draw_faders(frame: &mut Frame, area: Rect, lcxl2: Option<&LCXL2Driver>) {
+  let mut lines = Vec::new();
+  if let Some(lcxl2) = lcxl2 {
+    let value = |control: FaderControl| match lcxl2.value(control) {
+      Some(v) => format!("{:>4}", v),
+      None => "   -".to_string(),
+    };
+    let color = |position: LCXL2Position| match lcxl2.get_visual(position) {
+      Some(v) if v.to_rgb() != (0, 0, 0) => device_color(v.to_rgb()),
+      _ => Color::DarkGray,
+    };
+
+    for y in 1..=3 {
+      lines.push(Line::from(
+        (1..=8)
+          .map(|x| {
+            Span::styled(
+              value(FaderControl::Knob(x, y)),
+              color(LCXL2Position::Knob(x, y)),
+            )
+          })
+          .collect::<Vec<_>>(),
+      ));
+    }
+    lines.push(Line::from(
+      (1..=8)
+        .map(|x| Span::raw(value(FaderControl::Slider(x))))
+        .collect::<Vec<_>>(),
+    ));
+    for y in 1..=2 {
+      lines.push(Line::from(
+        (1..=8)
+          .map(|x| Span::styled("  ▆▆", color(LCXL2Position::Bottom(x, y))))
+          .collect::<Vec<_>>(),
+      ));
+    }
+  }
+
+  frame.render_widget(
+    Paragraph::new(lines).block(Block::bordered().title(" launch control ")),
+    area,
+  );
+}
+
+/// the last lines of the log that fit
+fn draw_log(frame: &mut Frame, area: Rect, log: &LogBuffer) {
+  let height = area.height.saturating_sub(2) as usize;
+  let lines = match log.0.lock() {
+    Ok(lines) => lines
+      .iter()
+      .skip(lines.len().saturating_sub(height))
+      .map(|l| Line::raw(l.clone()))
+      .collect::<Vec<_>>(),
+    Err(_) => Vec::new(),
+  };
+
+  frame.render_widget(
+    Paragraph::new(lines).block(Block::bordered().title(" log ")),
+    area,
+  );
+}
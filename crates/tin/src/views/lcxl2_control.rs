@@ -1,181 +1,225 @@
-use std::{collections::VecDeque, time::Duration};
+use std::{
+  collections::VecDeque,
+  time::{Duration, Instant},
+};
 
-use crate::{model::TinModel, servers::renoise::RenoiseCommunicator};
+use crate::{
+  model::TinModel,
+  servers::renoise::RenoiseCommunicator,
+  views::{FaderDevice, FaderDriver, View, ViewTransition},
+};
 use anyhow::Result;
 use intercom::server::{InterServerCommunicator, udp::UdpServer};
 use sophixer_core::{data::channels::Channel, messages::renoise::MessageToRenoise};
-use tin_drivers_midi::{
-  MidiDriver,
-  devices::launch_control_xl_mk2::{LCXL2Driver, LCXL2InputMessage, LCXL2Position, LCXL2Visual},
-};
+use tin_drivers_midi::surface::{FaderButton, FaderControl, FaderEvent, Light};
+
+const RED: Light = Light::Rgb(127, 0, 0);
+const GREEN: Light = Light::Rgb(0, 127, 0);
+const AMBER: Light = Light::Rgb(127, 127, 0);
+const YELLOW: Light = Light::Rgb(42, 127, 0);
 
 pub struct ViewLCXL2Control {}
 
+/// group of the layout bound to the faders and the bottom knob row
+const LEADS: &str = "lead";
+/// group of the layout bound to the top two knob rows
+const DRUMS: &str = "drum";
+
+/// renoise index of the n-th track of a group of the layout, or of its master track
+fn group_track(tin: &TinModel, group: &str, n: Option<u64>) -> Option<u64> {
+  let channel = match n {
+    Some(n) => Channel::Track(group.to_string(), n),
+    None => Channel::GroupMaster(group.to_string()),
+  };
+  tin.set.layout.resolve(&channel).ok()
+}
+
 impl ViewLCXL2Control {
   pub fn new(_tin: &TinModel) -> Self {
     Self {}
   }
+}
 
-  pub fn update(
+impl View<FaderDevice> for ViewLCXL2Control {
+  fn update(
     &mut self,
     _dt: &Duration,
     tin: &mut TinModel,
-    _lcxl2: &mut LCXL2Driver,
-    lcxl2_inputs: VecDeque<LCXL2InputMessage>,
+    _faders: &mut FaderDriver,
+    fader_inputs: VecDeque<FaderEvent>,
     server: &UdpServer,
-  ) -> Result<()> {
-    for i in lcxl2_inputs {
+  ) -> Result<ViewTransition<FaderDevice>> {
+    for i in fader_inputs {
       if let Some(rsa) = tin.renoise_socket {
         for x in 1..=6 {
-          if let Some(v) = i.has_analog_moved(LCXL2Position::Knob(x, 3)) {
+          if let Some(v) = i.moved(FaderControl::Knob(x, 3))
+            && let Some(track) = group_track(tin, LEADS, Some(x as u64))
+          {
             RenoiseCommunicator::send_message(
               server,
               rsa,
-              MessageToRenoise::SetParameterValue(
-                Channel::Lead(x as u64).to_renoise_number(),
-                2,
-                1,
-                (*v as f64) / 128.,
-              ),
+              MessageToRenoise::SetParameterValue(track, 2, 1, (v as f64) / 128.),
             )?;
           }
-          if let Some(v) = i.has_analog_moved(LCXL2Position::Knob(x, 1)) {
+          if let Some(v) = i.moved(FaderControl::Knob(x, 1))
+            && let Some(track) = group_track(tin, DRUMS, Some(x as u64))
+          {
             RenoiseCommunicator::send_message(
               server,
               rsa,
-              MessageToRenoise::SetParameterValue(
-                Channel::Drum(x as u64).to_renoise_number(),
-                2,
-                1,
-                (*v as f64) / 128.,
-              ),
+              MessageToRenoise::SetParameterValue(track, 2, 1, (v as f64) / 128.),
             )?;
           }
 
-          if let Some(v) = i.has_analog_moved(LCXL2Position::Knob(x, 2)) {
+          if let Some(v) = i.moved(FaderControl::Knob(x, 2))
+            && let Some(track) = group_track(tin, DRUMS, Some(x as u64))
+          {
             RenoiseCommunicator::send_message(
               server,
               rsa,
-              MessageToRenoise::SetParameterValue(
-                Channel::Drum(x as u64).to_renoise_number(),
-                3,
-                1,
-                (*v as f64) / 128.,
-              ),
+              MessageToRenoise::SetParameterValue(track, 3, 1, (v as f64) / 128.),
             )?;
           }
-          if let Some(v) = i.has_analog_moved(LCXL2Position::Slider(x)) {
+          if let Some(v) = i.moved(FaderControl::Slider(x))
+            && let Some(track) = group_track(tin, LEADS, Some(x as u64))
+          {
             RenoiseCommunicator::send_message(
               server,
               rsa,
-              MessageToRenoise::SetParameterValue(
-                Channel::Lead(x as u64).to_renoise_number(),
-                3,
-                1,
-                (*v as f64) / 128.,
-              ),
+              MessageToRenoise::SetParameterValue(track, 3, 1, (v as f64) / 128.),
             )?;
           }
         }
 
-        if let Some(v) = i.has_analog_moved(LCXL2Position::Knob(7, 3)) {
+        if let Some(v) = i.moved(FaderControl::Knob(7, 3))
+          && let Some(track) = group_track(tin, LEADS, None)
+        {
           RenoiseCommunicator::send_message(
             server,
             rsa,
-            MessageToRenoise::SetParameterValue(
-              Channel::MasterLead.to_renoise_number(),
-              2,
-              1,
-              (*v as f64) / 128.,
-            ),
+            MessageToRenoise::SetParameterValue(track, 2, 1, (v as f64) / 128.),
           )?;
         }
-        if let Some(v) = i.has_analog_moved(LCXL2Position::Knob(7, 1)) {
+        if let Some(v) = i.moved(FaderControl::Knob(7, 1))
+          && let Some(track) = group_track(tin, DRUMS, None)
+        {
           RenoiseCommunicator::send_message(
             server,
             rsa,
-            MessageToRenoise::SetParameterValue(
-              Channel::MasterDrum.to_renoise_number(),
-              2,
-              1,
-              (*v as f64) / 128.,
-            ),
+            MessageToRenoise::SetParameterValue(track, 2, 1, (v as f64) / 128.),
           )?;
         }
 
-        if let Some(v) = i.has_analog_moved(LCXL2Position::Knob(7, 2)) {
+        if let Some(v) = i.moved(FaderControl::Knob(7, 2))
+          && let Some(track) = group_track(tin, DRUMS, None)
+        {
           RenoiseCommunicator::send_message(
             server,
             rsa,
-            MessageToRenoise::SetParameterValue(
-              Channel::MasterDrum.to_renoise_number(),
-              3,
-              1,
-              (*v as f64) / 128.,
-            ),
+            MessageToRenoise::SetParameterValue(track, 3, 1, (v as f64) / 128.),
           )?;
         }
-        if let Some(v) = i.has_analog_moved(LCXL2Position::Slider(7)) {
+        if let Some(v) = i.moved(FaderControl::Slider(7))
+          && let Some(track) = group_track(tin, LEADS, None)
+        {
           RenoiseCommunicator::send_message(
             server,
             rsa,
-            MessageToRenoise::SetParameterValue(
-              Channel::MasterLead.to_renoise_number(),
-              3,
-              1,
-              (*v as f64) / 128.,
-            ),
+            MessageToRenoise::SetParameterValue(track, 3, 1, (v as f64) / 128.),
           )?;
         }
 
-        if let Some(v) = i.has_analog_moved(LCXL2Position::Knob(8, 3)) {
+        if let Some(v) = i.moved(FaderControl::Knob(8, 3)) {
           RenoiseCommunicator::send_message(
             server,
             rsa,
-            MessageToRenoise::SetParameterValue(
-              Channel::Master.to_renoise_number(),
-              2,
-              1,
-              (*v as f64) / 128.,
-            ),
+            MessageToRenoise::SetParameterValue(tin.set.layout.master, 2, 1, (v as f64) / 128.),
           )?;
         }
 
-        if let Some(v) = i.has_analog_moved(LCXL2Position::Slider(8)) {
+        if let Some(v) = i.moved(FaderControl::Slider(8)) {
           RenoiseCommunicator::send_message(
             server,
             rsa,
-            MessageToRenoise::SetParameterValue(
-              Channel::Master.to_renoise_number(),
-              3,
-              1,
-              (*v as f64) / 128.,
-            ),
+            MessageToRenoise::SetParameterValue(tin.set.layout.master, 3, 1, (v as f64) / 128.),
           )?;
         }
 
-        if let Some(v) = i.has_analog_moved(LCXL2Position::Knob(8, 2)) {
-          let bpm = tin.bpm + (*v as i64 - 64) as f64 * 0.5;
-          RenoiseCommunicator::send_message(server, rsa, MessageToRenoise::SetBPM(bpm))?;
+        if let Some(v) = i.moved(FaderControl::Knob(8, 1)) {
+          tin.morph_scenes(v as f64 / 127.)?;
+        }
+
+        // tempo
+        if let Some(v) = i.moved(FaderControl::Knob(8, 2))
+          && let Some(song) = tin
+            .current_song
+            .as_ref()
+            .and_then(|song_id| tin.set.songs.get(song_id))
+        {
+          // centered on the song's bpm
+          let bpm = song.bpm + (v as i64 - 64) as f64 * 0.5;
+          tin.tempo.set(bpm);
+        }
+        if i == FaderEvent::Pressed(FaderButton::Device)
+          && let Some(bpm) = tin.tempo.tap(Instant::now())
+        {
+          info!("tapped {:.2} bpm", bpm);
+        }
+        for (button, amount) in [
+          (FaderButton::Up, 1.),
+          (FaderButton::Down, -1.),
+          (FaderButton::Right, 0.1),
+          (FaderButton::Left, -0.1),
+        ] {
+          if i == FaderEvent::Pressed(button) {
+            tin.tempo.nudge(amount, Instant::now());
+          }
         }
       }
     }
 
-    Ok(())
+    Ok(ViewTransition::Stay)
   }
 
-  pub fn draw(&self, tin: &TinModel, lcxl2: &mut LCXL2Driver) -> Result<()> {
+  fn draw(&self, tin: &TinModel, faders: &mut FaderDriver) -> Result<()> {
     if tin.renoise_socket.is_some() {
       for x in 1..=7 {
-        lcxl2.add(LCXL2Visual::Static(LCXL2Position::Knob(x, 1), 3, 3))?;
+        faders.light_knob(x, 1, AMBER)?;
       }
       for x in 1..=7 {
-        lcxl2.add(LCXL2Visual::Static(LCXL2Position::Knob(x, 2), 3, 0))?;
+        faders.light_knob(x, 2, RED)?;
       }
       for x in 1..=8 {
-        lcxl2.add(LCXL2Visual::Static(LCXL2Position::Knob(x, 3), 0, 3))?;
+        faders.light_knob(x, 3, GREEN)?;
+      }
+      faders.light_knob(
+        8,
+        2,
+        match tin.tempo.is_ramping() {
+          false => YELLOW,
+          true => AMBER,
+        },
+      )?;
+
+      // tap, lit on the beat
+      if let Some(transport) = &tin.transport
+        && transport.playing
+        && transport.beats.fract() < 0.25
+      {
+        faders.light_button(FaderButton::Device, AMBER)?;
+      }
+      // nudges
+      for button in [
+        FaderButton::Up,
+        FaderButton::Down,
+        FaderButton::Left,
+        FaderButton::Right,
+      ] {
+        faders.light_button(button, RED)?;
+      }
+      if tin.recalled_scenes.len() == 2 {
+        faders.light_knob(8, 1, AMBER)?;
       }
-      lcxl2.add(LCXL2Visual::Static(LCXL2Position::Knob(8, 2), 1, 3))?;
     }
 
     Ok(())
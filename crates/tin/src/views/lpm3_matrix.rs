@@ -3,18 +3,21 @@ use std::{collections::VecDeque, time::Duration};
 use crate::{
   model::{LPM3View, TinModel},
   servers::renoise::RenoiseCommunicator,
+  views::{GridDevice, GridDriver, View, ViewTransition},
 };
 use anyhow::Result;
 use intercom::server::{InterServerCommunicator, udp::UdpServer};
-use sophixer_core::{data::buttons::ActionDescriptor, messages::renoise::MessageToRenoise};
-use tin_drivers_midi::{
-  MidiDriver, MidiPhysicalState,
-  devices::launchpad_mini_mk3::{LPM3Driver, LPM3InputMessage, LPM3Position, LPM3Visual},
+use sophixer_core::{
+  data::{
+    Song,
+    buttons::{ActionContext, ActionDescriptor},
+    quantization::Quantization,
+  },
+  messages::renoise::MessageToRenoise,
 };
+use tin_drivers_midi::surface::{GridButton, GridEvent, Light};
 
 pub struct ViewLPM3Matrix {
-  pub camera: (i64, i64),
-
   control: bool,
 
   insta_play: bool,
@@ -23,223 +26,344 @@ pub struct ViewLPM3Matrix {
 impl ViewLPM3Matrix {
   pub fn new() -> Self {
     Self {
-      camera: (0, 0),
       control: false,
       insta_play: false,
     }
   }
+}
+
+/// buttons of the top row, counting the beats of a bar where they're not lit otherwise
+const BEAT_COUNTER: [GridButton; 8] = [
+  GridButton::Up,
+  GridButton::Down,
+  GridButton::Left,
+  GridButton::Right,
+  GridButton::Page(1),
+  GridButton::Page(2),
+  GridButton::Page(3),
+  GridButton::Page(4),
+];
+
+/// a color darkened to a brightness between 0 and 1
+fn dimmed(color: [u8; 3], brightness: f64) -> Light {
+  let [r, g, b] = color.map(|c| (c as f64 * brightness).round() as u8);
+  Light::Rgb(r, g, b)
+}
+
+/// smallest and largest camera offsets along an axis, so the song's positions stay in reach
+///
+/// songs fitting on a page stay at the origin
+fn axis_bounds(positions: impl Iterator<Item = i64>, page: i64) -> (i64, i64) {
+  let Some((lo, hi)) = positions.fold(None, |bounds, p| match bounds {
+    None => Some((p, p)),
+    Some((lo, hi)) => Some((p.min(lo), p.max(hi))),
+  }) else {
+    return (0, 0);
+  };
+  let min = (lo - 1).min(0);
+  (min, (hi - page).max(min))
+}
 
-  pub fn update(
+/// camera bounds over a song's buttons and patterns, as (x, y) pairs of (min, max)
+fn camera_bounds(song: &Song, (columns, rows): (u8, u8)) -> ((i64, i64), (i64, i64)) {
+  let xs = song.buttons.keys().map(|(x, _)| *x);
+  let ys = song
+    .buttons
+    .keys()
+    .map(|(_, y)| *y)
+    .chain(song.patterns.keys().copied());
+  (
+    axis_bounds(xs, columns as i64),
+    axis_bounds(ys, rows as i64 - 1),
+  )
+}
+
+/// the camera kept within the song's bounds
+fn clamp_camera(camera: (i64, i64), song: &Song, size: (u8, u8)) -> (i64, i64) {
+  let ((min_x, max_x), (min_y, max_y)) = camera_bounds(song, size);
+  (camera.0.clamp(min_x, max_x), camera.1.clamp(min_y, max_y))
+}
+
+/// row of the grid showing a row of the song, the bottom row holds the controls
+fn to_row(camera: (i64, i64), rows: u8, by: i64) -> Option<u8> {
+  let y = by - camera.1;
+  (y >= 1 && y < rows as i64).then_some(y as u8)
+}
+
+/// pad showing a position of the song
+fn to_pad(camera: (i64, i64), (columns, rows): (u8, u8), (bx, by): (i64, i64)) -> Option<(u8, u8)> {
+  let x = bx - camera.0;
+  let y = to_row(camera, rows, by)?;
+  (x >= 1 && x <= columns as i64).then_some((x as u8, y))
+}
+
+impl View<GridDevice> for ViewLPM3Matrix {
+  fn update(
     &mut self,
     _dt: &Duration,
     tin: &mut TinModel,
-    lpm3: &mut LPM3Driver,
-    lpm3_inputs: VecDeque<LPM3InputMessage>,
+    grid: &mut GridDriver,
+    grid_inputs: VecDeque<GridEvent>,
     server: &UdpServer,
-  ) -> Result<()> {
+  ) -> Result<ViewTransition<GridDevice>> {
+    let mut transition = ViewTransition::Stay;
     let static_set = tin.set.clone();
-    for i in lpm3_inputs {
-      if i == LPM3InputMessage::KeyPressed(LPM3Position::Session) {
-        tin.lpm3view = LPM3View::SongList;
-      }
-
-      if let MidiPhysicalState::Binary(b) = lpm3.get_position_state(LPM3Position::SSM)? {
-        self.control = b;
+    // the bottom row holds the controls, the matrix is above it
+    let (columns, rows) = grid.size();
+    // a song or set change can leave the camera out of bounds
+    let song_bounds = match static_set.get_song_option(tin.current_song.clone())? {
+      Some(song) => {
+        tin.camera = clamp_camera(tin.camera, song, (columns, rows));
+        Some(camera_bounds(song, (columns, rows)))
       }
-      if let MidiPhysicalState::Binary(b) = lpm3.get_position_state(LPM3Position::Grid(1, 8))? {
-        self.insta_play = b && !self.control;
+      None => None,
+    };
+    for i in grid_inputs {
+      if i == GridEvent::Pressed(GridButton::Page(1)) {
+        transition = ViewTransition::Pop;
       }
 
-      if i == LPM3InputMessage::KeyPressed(LPM3Position::Left) {
-        self.camera.0 -= 1;
-      }
-      if i == LPM3InputMessage::KeyPressed(LPM3Position::Right) {
-        self.camera.0 += 1;
-      }
-      if i == LPM3InputMessage::KeyPressed(LPM3Position::Up) {
-        self.camera.1 -= 1;
-      }
-      if i == LPM3InputMessage::KeyPressed(LPM3Position::Down) {
-        self.camera.1 += 1;
+      self.control = grid.is_held(GridButton::Shift);
+      self.insta_play = grid.is_held(GridButton::Pad(1, rows)) && !self.control;
+
+      // arrows turn pages, one column or row at a time with ctrl
+      if let Some(((min_x, max_x), (min_y, max_y))) = song_bounds {
+        let (page_x, page_y) = match self.control {
+          true => (1, 1),
+          false => (columns as i64, rows as i64 - 1),
+        };
+        let (x, y) = tin.camera;
+        tin.camera = match i {
+          GridEvent::Pressed(GridButton::Left) => ((x - page_x).max(min_x), y),
+          GridEvent::Pressed(GridButton::Right) => ((x + page_x).min(max_x), y),
+          GridEvent::Pressed(GridButton::Up) => (x, (y - page_y).max(min_y)),
+          GridEvent::Pressed(GridButton::Down) => (x, (y + page_y).min(max_y)),
+          _ => (x, y),
+        };
       }
 
-      if let Some(rsa) = tin.renoise_socket {
-        if let Some(song_id) = tin.current_song.clone()
-          && let Some(song) = static_set.get_song_option(tin.current_song.clone())?
-        {
-          // control
-          if self.control {
-            if i == LPM3InputMessage::KeyPressed(LPM3Position::Grid(1, 8)) {
-              for ((bx, by), button) in &song.buttons {
-                let default = button.action.get_default();
-                tin
-                  .button_states
-                  .insert((song_id.clone(), *bx, *by), default);
-                let messages = button.action.create_renoise_message(default)?;
-                for m in messages {
-                  RenoiseCommunicator::send_message(server, rsa, m)?;
+      if let Some(rsa) = tin.renoise_socket
+        && let Some(song_id) = tin.current_song.clone()
+        && let Some(song) = static_set.get_song_option(tin.current_song.clone())?
+      {
+        // control
+        if self.control {
+          if i == GridEvent::Pressed(GridButton::Pad(1, rows)) {
+            let ctx = ActionContext {
+              layout: &static_set.layout,
+              devices: tin.devices_reported.then_some(&tin.devices),
+            };
+            for ((bx, by), button) in &song.buttons {
+              let default = button.action.get_default();
+              tin
+                .button_states
+                .insert((song_id.clone(), *bx, *by), default);
+              // stateless buttons, and the stateless steps of macros, would fire
+              match button.action.create_restore_renoise_message(default, &ctx) {
+                Ok(messages) => {
+                  for (_, m) in messages {
+                    RenoiseCommunicator::send_message(server, rsa, m)?;
+                  }
                 }
+                Err(e) => warn!("couldn't reset button {},{}: {}", bx, by, e),
               }
             }
-            if i == LPM3InputMessage::KeyPressed(LPM3Position::Grid(2, 8)) {
-              RenoiseCommunicator::send_message(server, rsa, MessageToRenoise::StopTransport)?;
-            }
-          } else {
-            if i == LPM3InputMessage::KeyPressed(LPM3Position::Grid(2, 8)) {
-              RenoiseCommunicator::send_message(
-                server,
-                rsa,
-                MessageToRenoise::PlaySection(tin.set.stop_seq_pos, false),
-              )?;
-              RenoiseCommunicator::send_message(
-                server,
-                rsa,
-                MessageToRenoise::SetLoop(tin.set.stop_seq_pos, tin.set.stop_seq_pos),
-              )?;
-            }
-            if i == LPM3InputMessage::KeyPressed(LPM3Position::Grid(3, 8)) {
-              tin.bpm = song.bpm;
-              RenoiseCommunicator::send_message(server, rsa, MessageToRenoise::SetBPM(tin.bpm))?;
-            }
           }
+          if i == GridEvent::Pressed(GridButton::Pad(2, rows)) {
+            RenoiseCommunicator::send_message(server, rsa, MessageToRenoise::StopTransport)?;
+          }
+        } else {
+          if i == GridEvent::Pressed(GridButton::Pad(2, rows)) {
+            tin.pattern = None;
+            RenoiseCommunicator::send_message(
+              server,
+              rsa,
+              MessageToRenoise::PlaySection(tin.set.stop_seq_pos, false),
+            )?;
+            RenoiseCommunicator::send_message(
+              server,
+              rsa,
+              MessageToRenoise::SetLoop(tin.set.stop_seq_pos, tin.set.stop_seq_pos),
+            )?;
+          }
+          if i == GridEvent::Pressed(GridButton::Pad(3, rows)) {
+            tin.tempo.set(song.bpm);
+          }
+        }
 
-          // patterns
-          for (by, pattern) in &song.patterns {
-            let y = *by - self.camera.1;
-            if y >= 1 && y <= 7 {
-              if i == LPM3InputMessage::KeyPressed(LPM3Position::Grid(9, y as u8)) {
-                RenoiseCommunicator::send_message(
-                  server,
-                  rsa,
-                  MessageToRenoise::PlaySection(pattern.start, self.insta_play),
-                )?;
-                RenoiseCommunicator::send_message(
-                  server,
-                  rsa,
-                  MessageToRenoise::SetLoop(pattern.loop_start, pattern.loop_end),
-                )?;
-                trace!(
-                  "playing pattern start {} loop_start {} loop_end {}",
-                  pattern.start, pattern.loop_start, pattern.loop_end
-                );
+        // scenes
+        for x in 4..=columns {
+          if i == GridEvent::Pressed(GridButton::Pad(x, rows)) {
+            let scene = (x - 4) as usize;
+            if self.control {
+              if let Err(e) = tin.save_scene(scene) {
+                warn!("couldn't save scene {}: {}", scene, e);
               }
+            } else if scene < song.scenes.len() {
+              tin.recall_scene(scene)?;
             }
           }
+        }
 
-          // buttons
-          for ((bx, by), button) in &song.buttons {
-            let x = *bx - self.camera.0;
-            let y = *by - self.camera.1;
-            if x >= 1 && x < 9 && y >= 1 && y < 9 {
-              if i == LPM3InputMessage::KeyPressed(LPM3Position::Grid(x as u8, y as u8)) {
-                // matrix button pressed
-                let key = (song_id.clone(), *bx, *by);
-                let current_state = tin
-                  .button_states
-                  .get(&key)
-                  .ok_or(anyhow::Error::msg("couldn't find state in model"))?;
-                let next = button.action.next(current_state.clone())?;
-                let messages = button.action.create_renoise_message(next)?;
-                for m in messages {
-                  RenoiseCommunicator::send_message(server, rsa, m)?;
-                }
-                tin.button_states.insert(key, next);
-              }
-            }
+        // patterns
+        for (by, pattern) in &song.patterns {
+          if let Some(y) = to_row(tin.camera, rows, *by)
+            && i == GridEvent::Pressed(GridButton::Scene(y))
+          {
+            // renoise schedules non-forced sections at the end of the pattern by itself
+            let (quantization, force) = match pattern.quantization {
+              _ if self.insta_play => (Quantization::Off, true),
+              Quantization::Pattern => (Quantization::Off, false),
+              q => (q, true),
+            };
+            tin.launch_pattern(&song_id, *by, quantization, force)?;
+          }
+        }
+
+        // buttons
+        for (bx, by) in song.buttons.keys() {
+          if let Some((x, y)) = to_pad(tin.camera, (columns, rows), (*bx, *by))
+            && i == GridEvent::Pressed(GridButton::Pad(x, y))
+          {
+            // matrix button pressed
+            tin.trigger_button(&song_id, (*bx, *by))?;
           }
         }
       }
     }
 
-    Ok(())
+    Ok(transition)
   }
 
-  pub fn draw(&self, tin: &TinModel, lpm3: &mut LPM3Driver) -> Result<()> {
+  fn draw(&self, tin: &TinModel, grid: &mut GridDriver) -> Result<()> {
+    let (columns, rows) = grid.size();
+
+    // beat counter, under the navigation: arrows and view buttons keep their lights
+    if let Some(transport) = tin.transport.as_ref().filter(|t| t.playing)
+      && let Some(button) = BEAT_COUNTER.get(
+        transport
+          .beat()
+          .rem_euclid(transport.beats_per_bar.max(1) as i64) as usize,
+      )
+    {
+      // the first beat of the bar stands out
+      let color = match button {
+        GridButton::Up => 21,
+        _ => 3,
+      };
+      grid.light(*button, Light::Palette(color))?;
+    }
+
     // navigation
-    lpm3.add(LPM3Visual::Static(LPM3Position::Logo, 53))?;
-    lpm3.add(LPM3Visual::Static(LPM3Position::Session, 45))?;
-    lpm3.add(LPM3Visual::Static(LPM3Position::Keys, 1))?;
+    grid.light(GridButton::Logo, Light::Palette(53))?;
+    grid.light(GridButton::Page(1), Light::Palette(45))?;
+    grid.light(GridButton::Page(3), Light::Palette(1))?;
 
     // control
-    lpm3.add(LPM3Visual::Static(
-      LPM3Position::SSM,
-      match self.control {
+    grid.light(
+      GridButton::Shift,
+      Light::Palette(match self.control {
         false => 13,
         true => 5,
-      },
-    ))?;
+      }),
+    )?;
 
     if let Some(song_id) = tin.current_song.clone()
       && let Some(song) = tin.set.songs.get(&song_id)
     {
       // CONTROL PANEL
+      // arrows light up where there's more of the song
+      let camera = clamp_camera(tin.camera, song, (columns, rows));
+      let ((min_x, max_x), (min_y, max_y)) = camera_bounds(song, (columns, rows));
       let directions = [
-        LPM3Position::Up,
-        LPM3Position::Down,
-        LPM3Position::Left,
-        LPM3Position::Right,
+        (GridButton::Up, camera.1 > min_y),
+        (GridButton::Down, camera.1 < max_y),
+        (GridButton::Left, camera.0 > min_x),
+        (GridButton::Right, camera.0 < max_x),
       ];
-      for d in directions {
-        lpm3.add(LPM3Visual::RGB(
-          d,
-          song.color[0],
-          song.color[1],
-          song.color[2],
-        ))?;
+      for (d, more) in directions {
+        if more {
+          grid.light(d, Light::Rgb(song.color[0], song.color[1], song.color[2]))?;
+        }
       }
 
       // control
       if tin.renoise_socket.is_some() {
         if self.control {
           // reset
-          lpm3.add(LPM3Visual::Static(LPM3Position::Grid(1, 8), 9))?;
+          grid.light(GridButton::Pad(1, rows), Light::Palette(9))?;
           // stop transport
-          lpm3.add(LPM3Visual::Static(LPM3Position::Grid(2, 8), 5))?;
+          grid.light(GridButton::Pad(2, rows), Light::Palette(5))?;
         } else {
           // instaplay
-          lpm3.add(LPM3Visual::Static(LPM3Position::Grid(1, 8), 69))?;
+          grid.light(GridButton::Pad(1, rows), Light::Palette(69))?;
           // go to break
-          lpm3.add(LPM3Visual::Static(LPM3Position::Grid(2, 8), 1))?;
+          grid.light(GridButton::Pad(2, rows), Light::Palette(1))?;
           // sync bpm
-          lpm3.add(LPM3Visual::Flashing(LPM3Position::Grid(3, 8), 5, 21))?;
+          grid.light(GridButton::Pad(3, rows), Light::Flashing(5, 21))?;
+        }
+      }
+
+      // scenes
+      if tin.renoise_socket.is_some() {
+        for x in 4..=columns {
+          let scene = (x - 4) as usize;
+          let button = GridButton::Pad(x, rows);
+          if self.control {
+            // save, over a scene or on the next free pad
+            if scene <= song.scenes.len() {
+              grid.light(button, Light::Palette(5))?;
+            }
+          } else if tin.recalled_scenes.last() == Some(&scene) {
+            grid.light(button, Light::Palette(21))?;
+          } else if scene < song.scenes.len() {
+            grid.light(button, Light::Palette(45))?;
+          }
         }
       }
 
       // sections
+      let transport = tin.transport.as_ref().filter(|t| t.playing);
+      let playing = tin.playing_pattern(&song_id);
+      let queued = tin.queued_pattern(&song_id);
       for (by, pattern) in &song.patterns {
-        let y = *by - self.camera.1;
-        if y >= 1 && y <= 7 {
+        if let Some(y) = to_row(camera, rows, *by) {
+          let light = match transport {
+            // playing: fades out over every beat
+            Some(t) if playing == Some(*by) => dimmed(pattern.color, 1. - 0.75 * t.beats.fract()),
+            // launched: flashes on every half beat, until renoise gets to it
+            Some(t) if queued == Some(*by) && (t.beats * 2.).floor() as i64 % 2 == 1 => Light::Off,
+            _ => dimmed(pattern.color, 1.),
+          };
           // section player
-          lpm3.add(LPM3Visual::RGB(
-            LPM3Position::Grid(9, y as u8),
-            pattern.color[0],
-            pattern.color[1],
-            pattern.color[2],
-          ))?;
+          grid.light(GridButton::Scene(y), light)?;
         }
       }
 
       // buttons
       for ((bx, by), button) in &song.buttons {
-        let x = *bx - self.camera.0;
-        let y = *by - self.camera.1;
-
-        if x >= 1 && x < 9 && y >= 1 && y < 8 {
+        if let Some((x, y)) = to_pad(camera, (columns, rows), (*bx, *by)) {
           let current_state = tin
             .button_states
             .get(&(song_id.clone(), *bx, *by))
             .ok_or(anyhow::Error::msg("couldn't find state in model"))?;
-          let color = button.action.get_color(current_state.clone())?;
-          lpm3.add(LPM3Visual::RGB(
-            LPM3Position::Grid(x as u8, y as u8),
-            color[0],
-            color[1],
-            color[2],
-          ))?;
+          // scripts light their buttons themselves
+          let color = match tin.scripts.light(&song_id, (*bx, *by)) {
+            Some(color) => color,
+            None => button.action.get_color(*current_state)?,
+          };
+          grid.light(
+            GridButton::Pad(x, y),
+            Light::Rgb(color[0], color[1], color[2]),
+          )?;
         }
       }
     }
 
     Ok(())
   }
+
+  fn enter(&mut self, tin: &mut TinModel) {
+    tin.lpm3view = LPM3View::Matrix;
+  }
 }
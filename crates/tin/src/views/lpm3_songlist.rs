@@ -3,156 +3,263 @@ use std::{collections::VecDeque, time::Duration};
 use crate::{
   model::{LPM3View, TinModel},
   servers::renoise::RenoiseCommunicator,
+  views::{
+    GridDevice, GridDriver, View, ViewTransition, lpm3_matrix::ViewLPM3Matrix, text::ScrollingText,
+  },
 };
 use anyhow::Result;
 use intercom::server::{InterServerCommunicator, udp::UdpServer};
-use sophixer_core::{data::buttons::ActionDescriptor, messages::renoise::MessageToRenoise};
-use tin_drivers_midi::{
-  MidiDriver, MidiPhysicalState,
-  devices::launchpad_mini_mk3::{LPM3Driver, LPM3InputMessage, LPM3Position, LPM3Visual},
+use sophixer_core::{
+  data::buttons::{ActionContext, ActionDescriptor},
+  messages::renoise::MessageToRenoise,
 };
+use tin_drivers_midi::surface::{GridButton, GridEvent, Light};
 
 pub struct ViewLPM3SongList {
   cached_song_list: Vec<String>,
+  page: usize,
+  /// setlist position the page last followed
+  followed_position: Option<usize>,
 
   control: bool,
-  print_song: bool,
+  show_info: bool,
+  /// name and bpm of the song pressed while holding info, in its color
+  info: Option<(ScrollingText, [u8; 3])>,
 }
 
 impl ViewLPM3SongList {
   pub fn new(tin: &TinModel) -> Self {
-    let mut song_list = tin
-      .set
-      .songs
-      .iter()
-      .map(|(id, s)| (id.clone(), s.order))
-      .collect::<Vec<(String, i64)>>();
-    song_list.sort_by(|a, b| a.1.cmp(&b.1));
-    let cached_song_list = song_list.iter().map(|(id, _)| id.clone()).collect();
-
     Self {
-      cached_song_list,
+      cached_song_list: tin.set.get_setlist(),
+      page: 0,
+      followed_position: None,
       control: false,
-      print_song: false,
+      show_info: false,
+      info: None,
     }
   }
 
-  pub fn update(
+  fn pages(&self, page_size: usize) -> usize {
+    self.cached_song_list.len().div_ceil(page_size).max(1)
+  }
+}
+
+/// songs on a page, the bottom row holds the controls
+fn page_size((columns, rows): (u8, u8)) -> usize {
+  columns as usize * (rows as usize - 1)
+}
+
+/// pad of a setlist position, if it's on the page
+fn to_pad(position: usize, page: usize, (columns, rows): (u8, u8)) -> Option<(u8, u8)> {
+  let i = position.checked_sub(page * page_size((columns, rows)))?;
+  let x = i % columns as usize + 1;
+  let y = i / columns as usize + 1;
+  (y < rows as usize).then_some((x as u8, y as u8))
+}
+
+/// songs other than the current one are dimmed
+fn dim(color: [u8; 3]) -> [u8; 3] {
+  color.map(|c| c.div_ceil(4))
+}
+
+impl View<GridDevice> for ViewLPM3SongList {
+  fn update(
     &mut self,
-    _dt: &Duration,
+    dt: &Duration,
     tin: &mut TinModel,
-    lpm3: &mut LPM3Driver,
-    lpm3_inputs: VecDeque<LPM3InputMessage>,
+    grid: &mut GridDriver,
+    grid_inputs: VecDeque<GridEvent>,
     server: &UdpServer,
-  ) -> Result<()> {
-    for i in lpm3_inputs {
-      if i == LPM3InputMessage::KeyPressed(LPM3Position::Keys) {
-        tin.lpm3view = LPM3View::Matrix;
-      }
+  ) -> Result<ViewTransition<GridDevice>> {
+    let mut transition = ViewTransition::Stay;
+    // the bottom row holds the controls, the songs are above it
+    let (columns, rows) = grid.size();
+    let page_size = page_size((columns, rows));
+    if let Some((text, _)) = &mut self.info {
+      text.update(dt);
+    }
 
-      if let MidiPhysicalState::Binary(b) = lpm3.get_position_state(LPM3Position::SSM)? {
-        self.control = b;
+    for i in grid_inputs {
+      if i == GridEvent::Pressed(GridButton::Page(3)) {
+        transition = ViewTransition::Push(Box::new(ViewLPM3Matrix::new()));
+      }
+      if i == GridEvent::Pressed(GridButton::Left) {
+        tin.step_song(-1)?;
+      }
+      if i == GridEvent::Pressed(GridButton::Right) {
+        tin.step_song(1)?;
+      }
+      if i == GridEvent::Pressed(GridButton::Up) {
+        self.page = self.page.saturating_sub(1);
+      }
+      if i == GridEvent::Pressed(GridButton::Down) {
+        self.page = (self.page + 1).min(self.pages(page_size) - 1);
       }
-      if let MidiPhysicalState::Binary(b) = lpm3.get_position_state(LPM3Position::Grid(1, 8))? {
-        self.print_song = b && !self.control;
+
+      self.control = grid.is_held(GridButton::Shift);
+      self.show_info = grid.is_held(GridButton::Pad(1, rows)) && !self.control;
+      if !self.show_info {
+        self.info = None;
       }
 
-      if let Some(rsa) = tin.renoise_socket {
-        if self.control {
-          if i == LPM3InputMessage::KeyPressed(LPM3Position::Grid(1, 8)) {
-            for (song_id, song) in &tin.set.songs {
-              for ((bx, by), button) in &song.buttons {
-                let default = button.action.get_default();
-                tin
-                  .button_states
-                  .insert((song_id.clone(), *bx, *by), default);
-                let messages = button.action.create_renoise_message(default)?;
-                for m in messages {
+      // reset all
+      if let Some(rsa) = tin.renoise_socket
+        && self.control
+        && i == GridEvent::Pressed(GridButton::Pad(1, rows))
+      {
+        let ctx = ActionContext {
+          layout: &tin.set.layout,
+          devices: tin.devices_reported.then_some(&tin.devices),
+        };
+        for (song_id, song) in &tin.set.songs {
+          for ((bx, by), button) in &song.buttons {
+            let default = button.action.get_default();
+            tin
+              .button_states
+              .insert((song_id.clone(), *bx, *by), default);
+            // stateless buttons, and the stateless steps of macros, would fire
+            match button.action.create_restore_renoise_message(default, &ctx) {
+              Ok(messages) => {
+                for (_, m) in messages {
                   RenoiseCommunicator::send_message(server, rsa, m)?;
                 }
               }
+              Err(e) => warn!("couldn't reset button {},{}: {}", bx, by, e),
             }
+          }
+        }
 
-            for x in 1..=15 {
-              for y in 2..=3 {
-                let mut v = 0.5;
-                if (x <= 7 || x == 15) && y == 3 {
-                  v = 1.0;
-                }
-                RenoiseCommunicator::send_message(
-                  server,
-                  rsa,
-                  MessageToRenoise::SetParameterValue(x, y, 1, v),
-                )?
-              }
+        // the first group and the master are fully open, everything else half way
+        let layout = &tin.set.layout;
+        let mut tracks = Vec::new();
+        for (g, group) in layout.groups.iter().enumerate() {
+          for track in group.tracks.iter().chain(group.master.iter()) {
+            tracks.push((*track, g == 0));
+          }
+        }
+        tracks.extend(layout.sends.iter().map(|s| (s.track, false)));
+        tracks.push((layout.master, true));
+
+        for (x, open) in tracks {
+          for y in 2..=3 {
+            let mut v = 0.5;
+            if open && y == 3 {
+              v = 1.0;
             }
+            RenoiseCommunicator::send_message(
+              server,
+              rsa,
+              MessageToRenoise::SetParameterValue(x, y, 1, v),
+            )?
           }
-        } else {
         }
       }
 
       for (p, song_id) in self.cached_song_list.iter().enumerate() {
-        if tin.set.songs.get(song_id).is_some() {
-          let x = (p % 8) + 1;
-          let y = p / 8 + 1;
-          if y < 8 && i == LPM3InputMessage::KeyPressed(LPM3Position::Grid(x as u8, y as u8)) {
-            if self.print_song {
-              if let Some(song) = tin.set.songs.get(song_id) {
-                info!(
-                  "selected song: {} by {} - BPM: {}",
-                  song.name, song.authors, song.bpm
-                );
-              }
-            } else {
-              tin.current_song = Some(song_id.clone());
-              info!("loaded song {}", song_id);
-            }
+        if let Some(song) = tin.set.songs.get(song_id)
+          && let Some((x, y)) = to_pad(p, self.page, (columns, rows))
+          && i == GridEvent::Pressed(GridButton::Pad(x, y))
+        {
+          if self.show_info {
+            let text = format!("{} {} bpm", song.name, song.bpm);
+            self.info = Some((ScrollingText::new(&text), song.color));
+          } else {
+            tin.switch_song(p)?;
           }
         }
       }
     }
 
-    Ok(())
+    // the page follows the current song when it changes
+    if tin.setlist_position != self.followed_position {
+      self.followed_position = tin.setlist_position;
+      if let Some(position) = tin.setlist_position {
+        self.page = position / page_size;
+      }
+    }
+    self.page = self.page.min(self.pages(page_size) - 1);
+
+    Ok(transition)
   }
 
-  pub fn draw(&self, tin: &TinModel, lpm3: &mut LPM3Driver) -> Result<()> {
+  fn draw(&self, tin: &TinModel, grid: &mut GridDriver) -> Result<()> {
+    let (columns, rows) = grid.size();
+
     // navigation
-    lpm3.add(LPM3Visual::Static(LPM3Position::Logo, 45))?;
-    lpm3.add(LPM3Visual::Static(LPM3Position::Session, 1))?;
-    lpm3.add(LPM3Visual::Static(LPM3Position::Keys, 53))?;
+    grid.light(GridButton::Logo, Light::Palette(45))?;
+    grid.light(GridButton::Page(1), Light::Palette(1))?;
+    grid.light(GridButton::Page(3), Light::Palette(53))?;
+
+    // setlist
+    grid.light(GridButton::Left, Light::Palette(45))?;
+    grid.light(GridButton::Right, Light::Palette(45))?;
+    // pages, lit where there are more songs
+    if self.page > 0 {
+      grid.light(GridButton::Up, Light::Palette(45))?;
+    }
+    if self.page + 1 < self.pages(page_size((columns, rows))) {
+      grid.light(GridButton::Down, Light::Palette(45))?;
+    }
 
     // control
-    lpm3.add(LPM3Visual::Static(
-      LPM3Position::SSM,
-      match self.control {
+    grid.light(
+      GridButton::Shift,
+      Light::Palette(match self.control {
         false => 13,
         true => 5,
-      },
-    ))?;
+      }),
+    )?;
 
-    if let Some(_rsa) = &tin.renoise_socket {
-      if self.control {
+    if self.control {
+      if tin.renoise_socket.is_some() {
         // reset all
-        lpm3.add(LPM3Visual::Static(LPM3Position::Grid(1, 8), 10))?;
-      } else {
+        grid.light(GridButton::Pad(1, rows), Light::Palette(10))?;
       }
+    } else {
+      // info
+      grid.light(
+        GridButton::Pad(1, rows),
+        Light::Palette(match self.show_info {
+          false => 45,
+          true => 21,
+        }),
+      )?;
     }
 
-    for (i, song_id) in self.cached_song_list.iter().enumerate() {
-      if let Some(song) = tin.set.songs.get(song_id) {
-        let x = (i % 8) + 1;
-        let y = i / 8 + 1;
-        if y < 8 {
-          lpm3.add(LPM3Visual::RGB(
-            LPM3Position::Grid(x as u8, y as u8),
-            song.color[0],
-            song.color[1],
-            song.color[2],
-          ))?;
-        }
+    if let Some((text, color)) = &self.info {
+      return text.draw(grid, rows - 1, Light::Rgb(color[0], color[1], color[2]));
+    }
+
+    // the song playing is lit, the one switched to pulses until its transition's grid
+    let (playing, queued) = match tin.queued_song() {
+      Some(switch) => (switch.previous, Some(switch.position)),
+      None => (tin.setlist_position, None),
+    };
+    for (p, song_id) in self.cached_song_list.iter().enumerate() {
+      if let Some(song) = tin.set.songs.get(song_id)
+        && let Some((x, y)) = to_pad(p, self.page, (columns, rows))
+      {
+        let light = match Some(p) {
+          p if p == queued => Light::Pulsing(21),
+          p if p == playing => Light::Rgb(song.color[0], song.color[1], song.color[2]),
+          _ => {
+            let [r, g, b] = dim(song.color);
+            Light::Rgb(r, g, b)
+          }
+        };
+        grid.light(GridButton::Pad(x, y), light)?;
       }
     }
 
     Ok(())
   }
+
+  fn enter(&mut self, tin: &mut TinModel) {
+    tin.lpm3view = LPM3View::SongList;
+  }
+
+  /// reads the setlist again
+  fn reload(&mut self, tin: &TinModel) {
+    self.cached_song_list = tin.set.get_setlist();
+  }
 }
@@ -1,3 +1,133 @@
+//! pages shown on the control surfaces
+//!
+//! every device has a stack of views: the top one gets the inputs,
+//! and is drawn over the views under it if it's modal
+
 pub mod lcxl2_control;
 pub mod lpm3_matrix;
 pub mod lpm3_songlist;
+pub mod text;
+
+use std::{collections::VecDeque, time::Duration};
+
+use anyhow::Result;
+use intercom::server::udp::UdpServer;
+use tin_drivers_midi::surface::{FaderEvent, FaderSurface, GridEvent, GridSurface};
+
+use crate::model::TinModel;
+
+/// a kind of control surface views are shown on
+pub trait Device {
+  type Driver: ?Sized;
+  type Input: Clone;
+}
+
+pub type GridDriver = dyn GridSurface;
+pub type FaderDriver = dyn FaderSurface;
+
+/// pads with buttons around them, like the launchpad
+pub struct GridDevice {}
+impl Device for GridDevice {
+  type Driver = GridDriver;
+  type Input = GridEvent;
+}
+
+/// knobs and sliders, like the launch control
+pub struct FaderDevice {}
+impl Device for FaderDevice {
+  type Driver = FaderDriver;
+  type Input = FaderEvent;
+}
+
+/// what a view does to its stack after an update
+pub enum ViewTransition<D: Device> {
+  Stay,
+  /// shows a view over this one
+  Push(Box<dyn View<D>>),
+  /// goes back to the view under this one
+  Pop,
+}
+
+pub trait View<D: Device> {
+  fn update(
+    &mut self,
+    dt: &Duration,
+    tin: &mut TinModel,
+    driver: &mut D::Driver,
+    inputs: VecDeque<D::Input>,
+    server: &UdpServer,
+  ) -> Result<ViewTransition<D>>;
+
+  fn draw(&self, tin: &TinModel, driver: &mut D::Driver) -> Result<()>;
+
+  /// the view became the top of its stack
+  fn enter(&mut self, _tin: &mut TinModel) {}
+
+  /// the set got reloaded
+  fn reload(&mut self, _tin: &TinModel) {}
+
+  /// modal views are drawn over the views under them, which get no inputs meanwhile
+  fn is_modal(&self) -> bool {
+    false
+  }
+}
+
+pub struct ViewStack<D: Device> {
+  views: Vec<Box<dyn View<D>>>,
+}
+
+impl<D: Device> ViewStack<D> {
+  pub fn new(mut root: Box<dyn View<D>>, tin: &mut TinModel) -> Self {
+    root.enter(tin);
+    Self { views: vec![root] }
+  }
+
+  pub fn push(&mut self, mut view: Box<dyn View<D>>, tin: &mut TinModel) {
+    view.enter(tin);
+    self.views.push(view);
+  }
+
+  /// the root view stays
+  pub fn pop(&mut self, tin: &mut TinModel) {
+    if self.views.len() > 1 {
+      self.views.pop();
+    }
+    if let Some(top) = self.views.last_mut() {
+      top.enter(tin);
+    }
+  }
+
+  pub fn update(
+    &mut self,
+    dt: &Duration,
+    tin: &mut TinModel,
+    driver: &mut D::Driver,
+    inputs: VecDeque<D::Input>,
+    server: &UdpServer,
+  ) -> Result<()> {
+    let Some(top) = self.views.last_mut() else {
+      return Ok(());
+    };
+    match top.update(dt, tin, driver, inputs, server)? {
+      ViewTransition::Stay => {}
+      ViewTransition::Push(view) => self.push(view, tin),
+      ViewTransition::Pop => self.pop(tin),
+    }
+    Ok(())
+  }
+
+  /// draws the top view, over the views it's modal to
+  pub fn draw(&self, tin: &TinModel, driver: &mut D::Driver) -> Result<()> {
+    let bottom = self.views.iter().rposition(|v| !v.is_modal()).unwrap_or(0);
+    for view in &self.views[bottom..] {
+      view.draw(tin, driver)?;
+    }
+    Ok(())
+  }
+
+  pub fn reload(&mut self, tin: &TinModel) {
+    for view in &mut self.views {
+      view.reload(tin);
+    }
+  }
+}
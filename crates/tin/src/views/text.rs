@@ -0,0 +1,136 @@
+//! text scrolling across a grid, in a 3x5 font
+
+use std::time::Duration;
+
+use anyhow::Result;
+use tin_drivers_midi::surface::{GridButton, Light};
+
+use crate::views::GridDriver;
+
+/// columns scrolled per second
+const SCROLL_SPEED: f64 = 10.;
+/// rows of the glyphs
+const HEIGHT: u8 = 5;
+
+/// shown for characters the font doesn't have
+const UNKNOWN: [&str; 5] = ["##.", "..#", ".#.", "...", ".#."];
+
+/// glyphs from their top row down
+const GLYPHS: &[(char, [&str; 5])] = &[
+  ('A', [".#.", "#.#", "###", "#.#", "#.#"]),
+  ('B', ["##.", "#.#", "##.", "#.#", "##."]),
+  ('C', [".##", "#..", "#..", "#..", ".##"]),
+  ('D', ["##.", "#.#", "#.#", "#.#", "##."]),
+  ('E', ["###", "#..", "##.", "#..", "###"]),
+  ('F', ["###", "#..", "##.", "#..", "#.."]),
+  ('G', [".##", "#..", "#.#", "#.#", ".##"]),
+  ('H', ["#.#", "#.#", "###", "#.#", "#.#"]),
+  ('I', ["###", ".#.", ".#.", ".#.", "###"]),
+  ('J', ["..#", "..#", "..#", "#.#", ".#."]),
+  ('K', ["#.#", "#.#", "##.", "#.#", "#.#"]),
+  ('L', ["#..", "#..", "#..", "#..", "###"]),
+  ('M', ["#.#", "###", "###", "#.#", "#.#"]),
+  ('N', ["##.", "#.#", "#.#", "#.#", "#.#"]),
+  ('O', [".#.", "#.#", "#.#", "#.#", ".#."]),
+  ('P', ["##.", "#.#", "##.", "#..", "#.."]),
+  ('Q', [".#.", "#.#", "#.#", "##.", ".##"]),
+  ('R', ["##.", "#.#", "##.", "#.#", "#.#"]),
+  ('S', [".##", "#..", ".#.", "..#", "##."]),
+  ('T', ["###", ".#.", ".#.", ".#.", ".#."]),
+  ('U', ["#.#", "#.#", "#.#", "#.#", "###"]),
+  ('V', ["#.#", "#.#", "#.#", "#.#", ".#."]),
+  ('W', ["#.#", "#.#", "###", "###", "#.#"]),
+  ('X', ["#.#", "#.#", ".#.", "#.#", "#.#"]),
+  ('Y', ["#.#", "#.#", ".#.", ".#.", ".#."]),
+  ('Z', ["###", "..#", ".#.", "#..", "###"]),
+  ('0', ["###", "#.#", "#.#", "#.#", "###"]),
+  ('1', [".#.", "##.", ".#.", ".#.", "###"]),
+  ('2', ["##.", "..#", ".#.", "#..", "###"]),
+  ('3', ["##.", "..#", ".#.", "..#", "##."]),
+  ('4', ["#.#", "#.#", "###", "..#", "..#"]),
+  ('5', ["###", "#..", "##.", "..#", "##."]),
+  ('6', [".##", "#..", "###", "#.#", "###"]),
+  ('7', ["###", "..#", ".#.", ".#.", ".#."]),
+  ('8', ["###", "#.#", "###", "#.#", "###"]),
+  ('9', ["###", "#.#", "###", "..#", "##."]),
+  (' ', ["...", "...", "...", "...", "..."]),
+  ('-', ["...", "...", "###", "...", "..."]),
+  ('.', ["...", "...", "...", "...", ".#."]),
+  (',', ["...", "...", "...", ".#.", "#.."]),
+  (':', ["...", ".#.", "...", ".#.", "..."]),
+  ('!', [".#.", ".#.", ".#.", "...", ".#."]),
+  ('?', UNKNOWN),
+  ('\'', [".#.", ".#.", "...", "...", "..."]),
+  ('&', [".#.", "#.#", ".#.", "#.#", ".##"]),
+  ('/', ["..#", "..#", ".#.", "#..", "#.."]),
+  ('(', ["..#", ".#.", ".#.", ".#.", "..#"]),
+  (')', ["#..", ".#.", ".#.", ".#.", "#.."]),
+];
+
+fn glyph(c: char) -> &'static [&'static str; 5] {
+  let c = c.to_ascii_uppercase();
+  GLYPHS
+    .iter()
+    .find(|(g, _)| *g == c)
+    .map_or(&UNKNOWN, |(_, rows)| rows)
+}
+
+/// text going right to left over the grid, starting again once it went through
+pub struct ScrollingText {
+  /// lit rows of every column, bit 0 at the top
+  columns: Vec<u8>,
+  elapsed: Duration,
+}
+
+impl ScrollingText {
+  pub fn new(text: &str) -> Self {
+    let mut columns = Vec::new();
+    for c in text.chars() {
+      let rows = glyph(c);
+      for x in 0..3 {
+        let mut column = 0;
+        for (y, row) in rows.iter().enumerate() {
+          if row.as_bytes()[x] == b'#' {
+            column |= 1 << y;
+          }
+        }
+        columns.push(column);
+      }
+      // spacing
+      columns.push(0);
+    }
+    Self {
+      columns,
+      elapsed: Duration::ZERO,
+    }
+  }
+
+  pub fn update(&mut self, dt: &Duration) {
+    self.elapsed += *dt;
+  }
+
+  /// lights the text over the pads of rows 1 to `rows`, centered, the other pads are left alone
+  pub fn draw(&self, grid: &mut GridDriver, rows: u8, light: Light) -> Result<()> {
+    let (columns, _) = grid.size();
+    // the text comes in from the right edge
+    let length = self.columns.len() + columns as usize;
+    let scrolled = (self.elapsed.as_secs_f64() * SCROLL_SPEED) as usize % length;
+    let top = rows.saturating_sub(HEIGHT) / 2 + 1;
+
+    for x in 1..=columns {
+      let Some(column) = (scrolled + x as usize)
+        .checked_sub(columns as usize + 1)
+        .and_then(|i| self.columns.get(i))
+      else {
+        continue;
+      };
+      for y in 0..HEIGHT.min(rows) {
+        if column & (1 << y) != 0 {
+          grid.light(GridButton::Pad(x, top + y), light)?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
@@ -1,3 +1,8 @@
+---@param path string
+function load_song(path)
+  renoise.app():load_song(path)
+end
+
 ---@param position number
 function schedule_sequence(position)
   renoise.song().transport:set_scheduled_sequence(position)
@@ -2,19 +2,26 @@ class "Client"
   function Client:__init()
   	self.socket = renoise.Socket.create_client("localhost", 3000, 2)
     self.connected = false
+    self.last_transport = nil
+    self.last_bpm = nil
+    -- a loaded song has other devices, saying hello again makes tin ask for them
+    self.document_notifier = function()
+      self:send("hello")
+    end
+    renoise.tool().app_new_document_observable:add_notifier(self.document_notifier)
     self:send("hello")
     renoise.app():show_status("attempting to connect to tin... is tin running?")
   end
 
   function Client:destroy()
     self:send("goodbye")
+    renoise.tool().app_new_document_observable:remove_notifier(self.document_notifier)
   	self.socket = nil
     self.connected = false
   end
 
   function Client:send(msg)
   	if self.socket then
-      print("sending: " .. msg)
   		local success, error = self.socket:send("calcium:" .. msg .. ";")
   		if not success then
   			warn("couldn't send message to server: " .. error)
@@ -26,7 +33,12 @@ class "Client"
     local sub = string_split(msg, ",")
     if #sub == 1 then
       if sub[1] == "welcome" then
+        self.connected = true
+        self.last_bpm = nil
         renoise.app():show_status("connected to tin!")
+        self:report_devices()
+      elseif sub[1] == "startTransport" then
+        renoise.song().transport:start(renoise.Transport.PLAYMODE_CONTINUE_PATTERN)
       elseif sub[1] == "stopTransport" then
         renoise.song().transport:stop()
       end
@@ -36,12 +48,19 @@ class "Client"
         if bpm ~= nil then
           renoise.song().transport.bpm = bpm
         end
+      elseif sub[1] == "loadSong" then
+        load_song(sub[2])
       elseif sub[1] == "setMasterVolume" then
         local vol = tonumber(sub[2])
         if vol ~= nil then
           local master = renoise.song():track(renoise.song().sequencer_track_count + 1)
           master.postfx_volume.value = vol
         end
+      elseif sub[1] == "setPlaybackBeats" then
+        local beats = tonumber(sub[2])
+        if beats ~= nil then
+          renoise.song().transport.playback_pos_beats = beats
+        end
       end
     elseif #sub == 3 then
       if sub[1] == "muteTrack" then
@@ -108,6 +127,58 @@ class "Client"
     end
   end
   
+  -- tin resolves device and parameter names with this, so it is sent on connect
+  function Client:report_devices()
+    -- names can't contain the message separators
+    local function sanitize(name)
+      return (string.gsub(name, "[,;:]", "_"))
+    end
+
+    for t, track in ipairs(renoise.song().tracks) do
+      for d, device in ipairs(track.devices) do
+        self:send(string.format("device,%d,%d,%s", t, d, sanitize(device.display_name)))
+        for p, parameter in ipairs(device.parameters) do
+          self:send(string.format("parameter,%d,%d,%d,%s", t, d, p, sanitize(parameter.name)))
+        end
+      end
+    end
+    self:send("devicesReported")
+  end
+
+  -- tin quantizes its actions on this, so it is sent whenever the line changes
+  -- beats move on every idle tick while playing, they don't make a report on their own
+  function Client:report_transport()
+    local transport = renoise.song().transport
+    local pos = transport.playback_pos
+    local position = string.format(
+      "%d,%d,%d,%d",
+      transport.playing and 1 or 0,
+      pos.sequence,
+      pos.line,
+      transport.metronome_beats_per_bar
+    )
+    if position ~= self.last_transport then
+      self.last_transport = position
+      self:send(string.format(
+        "transport,%d,%d,%d,%f,%d",
+        transport.playing and 1 or 0,
+        pos.sequence,
+        pos.line,
+        transport.playback_pos_beats,
+        transport.metronome_beats_per_bar
+      ))
+    end
+  end
+
+  -- tin reconciles its tempo with the one renoise plays at
+  function Client:report_bpm()
+    local bpm = renoise.song().transport.bpm
+    if bpm ~= self.last_bpm then
+      self.last_bpm = bpm
+      self:send(string.format("bpm,%f", bpm))
+    end
+  end
+
   function Client:callback()
     if self.socket then
       ---@type string|nil
@@ -122,5 +193,9 @@ class "Client"
           end
         end
       end
+      if self.connected then
+        self:report_transport()
+        self:report_bpm()
+      end
     end
   end